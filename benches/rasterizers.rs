@@ -0,0 +1,137 @@
+#[macro_use]
+extern crate criterion;
+extern crate graphic_library;
+extern crate raster;
+
+use std::hint::black_box;
+use criterion::{BenchmarkId, Criterion};
+use raster::Color;
+use graphic_library::geometric::{Circle2D, Geometric2D, Line2D, Point2D, Transform2D, Triangle2D};
+use graphic_library::graphic::{Antialiasing, DownsampleFilter, Framebuffer, Renderer, SamplePattern};
+
+/// Edge lengths of the Geometric-Objects in pixels
+const SIZES: [f64; 3] = [16.0, 128.0, 1024.0];
+
+/// Runs every draw method of a Geometric-Object at every size in `SIZES`
+///
+/// # Arguments
+///
+/// * `c` - Criterion collecting the measurements
+/// * `name` - Name of the benchmark group
+/// * `shape` - Returns the Geometric-Object of a size, placed inside a canvas one pixel larger
+///
+fn draw_methods<G: Geometric2D>(c: &mut Criterion, name: &str, shape: fn(f64) -> G) {
+    let mut group = c.benchmark_group(name);
+    for &size in &SIZES {
+        let geo = shape(size);
+        let mut canvas = Framebuffer::new(size as i32 + 1, size as i32 + 1);
+        group.bench_with_input(BenchmarkId::new("draw", size), &geo, |b, geo| b.iter(|| black_box(geo).draw(&mut canvas)));
+        group.bench_with_input(BenchmarkId::new("draw_aa", size), &geo, |b, geo| b.iter(|| black_box(geo).draw_aa(&mut canvas)));
+        group.bench_with_input(BenchmarkId::new("draw_outline", size), &geo, |b, geo| b.iter(|| black_box(geo).draw_outline(&mut canvas)));
+    }
+    group.finish();
+}
+
+fn lines(c: &mut Criterion) {
+    draw_methods(c, "Line2D", |size| Line2D::new(Point2D::new_color(0.0, 0.0, Color::red()),
+                                                  Point2D::new_color(size, size * 0.6, Color::blue())));
+}
+
+fn triangles(c: &mut Criterion) {
+    draw_methods(c, "Triangle2D", |size| Triangle2D::new(Point2D::new_color(0.0, 0.0, Color::red()),
+                                                          Point2D::new_color(size, size * 0.3, Color::green()),
+                                                          Point2D::new_color(size * 0.4, size, Color::blue())));
+}
+
+fn circles(c: &mut Criterion) {
+    draw_methods(c, "Circle2D", |size| Circle2D::new(size / 2.0, Point2D::new_color(size / 2.0, size / 2.0, Color::red())));
+}
+
+fn transforms(c: &mut Criterion) {
+    let points: Vec<Point2D> = (0..100_000).map(|i| Point2D::new((i % 1000) as f64, (i / 1000) as f64)).collect();
+    let center = Point2D::new(500.0, 50.0);
+    let transform = Transform2D::rotation_from_point(30.0, &center).then(&Transform2D::scaling(2.0, 0.5));
+    let mut group = c.benchmark_group("Transform2D");
+    group.bench_function("apply_transform 100000 Points", |b| b.iter(|| {
+        let mut points = points.clone();
+        for p in &mut points {
+            p.apply_transform(black_box(&transform));
+        }
+        points
+    }));
+    group.bench_function("rotate_from_point 100000 Points", |b| b.iter(|| {
+        let mut points = points.clone();
+        for p in &mut points {
+            p.rotate_from_point(black_box(30.0), &center);
+        }
+        points
+    }));
+    group.bench_function("apply 100000 coordinates", |b| b.iter(|| {
+        points.iter().map(|p| transform.apply(p.x, p.y)).fold(0.0, |sum, (x, y)| sum + x + y)
+    }));
+    group.finish();
+}
+
+/// Returns a Renderer with a scene of overlapping Triangles, Circles and Lines
+///
+/// # Arguments
+///
+/// * `size` - Width and height of the frame, the scene is laid out for 512 and scaled to it
+///
+fn scene(size: i32) -> Renderer {
+    let mut renderer = Renderer::new(size, size);
+    let s = size as f64 / 512.0;
+    for i in 0..50 {
+        let (x, y) = ((i * 97 % 450) as f64 * s, (i * 61 % 450) as f64 * s);
+        let color = Color::rgba((i * 40 % 256) as u8, (i * 90 % 256) as u8, (i * 20 % 256) as u8, 200);
+        renderer.add(Triangle2D::new(Point2D::new_color(x, y, color.clone()),
+                                     Point2D::new_color(x + 60.0 * s, y + 10.0 * s, Color::green()),
+                                     Point2D::new_color(x + 20.0 * s, y + 55.0 * s, Color::blue())));
+        renderer.add(Circle2D::new(25.0 * s, Point2D::new_color(x + 30.0 * s, y + 30.0 * s, color.clone())));
+        renderer.add(Line2D::new(Point2D::new_color(x, y + 60.0 * s, color), Point2D::new_color(x + 60.0 * s, y, Color::white())));
+    }
+    renderer
+}
+
+fn frames(c: &mut Criterion) {
+    let modes = [("Off", Antialiasing::Off),
+                 ("Supersampling 2 Box", Antialiasing::Supersampling { factor: 2, filter: DownsampleFilter::Box }),
+                 ("Supersampling 4 Lanczos", Antialiasing::Supersampling { factor: 4, filter: DownsampleFilter::Lanczos }),
+                 ("Multisampling", Antialiasing::Multisampling(SamplePattern::default()))];
+    let mut group = c.benchmark_group("Renderer 512x512 150 shapes");
+    group.sample_size(10);
+    for (name, mode) in modes.iter() {
+        let mut renderer = scene(512);
+        renderer.set_antialiasing(mode.clone());
+        group.bench_function(*name, |b| b.iter(|| renderer.render_frame()));
+    }
+    let mut renderer = scene(512);
+    renderer.set_depth_test(true);
+    group.bench_function("Depth test", |b| b.iter(|| renderer.render_frame()));
+    #[cfg(feature = "parallel")]
+    {
+        let mut renderer = scene(512);
+        renderer.set_parallel(true);
+        group.bench_function("Parallel", |b| b.iter(|| renderer.render_frame()));
+    }
+    group.finish();
+}
+
+/// Compares serial and tiled drawing of a frame large enough to keep every thread busy
+#[cfg(feature = "parallel")]
+fn large_frames(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Renderer 4096x4096 150 shapes");
+    group.sample_size(10);
+    for &parallel in &[false, true] {
+        let mut renderer = scene(4096);
+        renderer.set_parallel(parallel);
+        group.bench_function(if parallel { "Parallel" } else { "Serial" }, |b| b.iter(|| renderer.render_frame()));
+    }
+    group.finish();
+}
+
+#[cfg(not(feature = "parallel"))]
+criterion_group!(benches, lines, triangles, circles, transforms, frames);
+#[cfg(feature = "parallel")]
+criterion_group!(benches, lines, triangles, circles, transforms, frames, large_frames);
+criterion_main!(benches);
@@ -0,0 +1,150 @@
+#[macro_use]
+extern crate criterion;
+extern crate graphic_library;
+extern crate raster;
+
+use std::hint::black_box;
+use criterion::Criterion;
+use raster::Color;
+use graphic_library::geometric::{Geometric2D, Point2D, Triangle2D};
+use graphic_library::graphic::{Background, Canvas, ColorSpace, Framebuffer};
+use graphic_library::graphic::simd::{self, Isa};
+
+const SIZE: i32 = 1024;
+
+/// Edge length of the Triangle in the commented example of `main.rs`
+const LARGE: i32 = 10000;
+
+/// Canvas only offering pixel access, so every blend and span takes the per-pixel get, blend and set path
+struct PerPixel<'a>(&'a mut Framebuffer);
+
+impl<'a> Canvas for PerPixel<'a> {
+    fn width(&self) -> i32 {
+        self.0.width()
+    }
+
+    fn height(&self) -> i32 {
+        self.0.height()
+    }
+
+    fn get_pixel(&self, x: i32, y: i32) -> Option<Color> {
+        self.0.get_pixel(x, y)
+    }
+
+    fn set_pixel(&mut self, x: i32, y: i32, color: &Color) {
+        self.0.set_pixel(x, y, color);
+    }
+}
+
+/// Blends an opaque square with the per-pixel get, blend and set path, pixel by pixel and row by row
+fn blend_rows(c: &mut Criterion) {
+    let color = Color::rgb(200, 100, 30);
+    let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+    c.bench_function("get/blend/set 1024x1024", |b| b.iter(|| {
+        let mut canvas = PerPixel(&mut framebuffer);
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                canvas.blend_pixel(x, y, black_box(&color), 1.0);
+            }
+        }
+    }));
+    c.bench_function("blend_pixel 1024x1024", |b| b.iter(|| {
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                framebuffer.blend_pixel(x, y, black_box(&color), 1.0);
+            }
+        }
+    }));
+    c.bench_function("blend_span 1024x1024", |b| b.iter(|| {
+        for y in 0..SIZE {
+            framebuffer.blend_span(0, SIZE, y, black_box(&color), 1.0);
+        }
+    }));
+}
+
+/// Translucent colors and overlays are blended by the vector kernels where the processor supports them
+fn composite(c: &mut Criterion) {
+    let color = Color::rgba(200, 100, 30, 128);
+    //Every pixel differs from its neighbour, so no blended pixel can be reused
+    let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+    for (i, byte) in framebuffer.as_bytes_mut().iter_mut().enumerate() {
+        *byte = (i * 7 % 251) as u8;
+    }
+    let mut overlay = framebuffer.clone();
+    overlay.clear(&Color::rgba(30, 200, 100, 100));
+    c.bench_function("translucent blend_span 1024x1024", |b| b.iter(|| {
+        for y in 0..SIZE {
+            framebuffer.blend_span(0, SIZE, y, black_box(&color), 0.75);
+        }
+    }));
+    c.bench_function("composite 1024x1024", |b| b.iter(|| framebuffer.composite(black_box(&overlay), 0.5)));
+}
+
+/// Every kernel on every instruction set the processor supports, on the same rows of pixels
+fn kernels(c: &mut Criterion) {
+    //Every pixel differs from its neighbour, so no blended pixel can be reused
+    let under: Vec<u8> = (0..SIZE as usize * 4).map(|i| (i * 7 % 251) as u8).collect();
+    let over: Vec<u8> = (0..SIZE as usize * 4).map(|i| (i * 13 % 241) as u8).collect();
+    let mut row = under.clone();
+    for isa in Isa::supported() {
+        let mut group = c.benchmark_group(format!("{} kernels 1024 pixels", isa.name()));
+        group.bench_function("fill", |b| b.iter(|| simd::fill(isa, &mut row, black_box([1, 2, 3, 4]))));
+        group.bench_function("blend", |b| b.iter(|| {
+            row.copy_from_slice(&under);
+            simd::blend(isa, &mut row, black_box(&[200, 100, 30, 128]), 0.75, false);
+        }));
+        group.bench_function("composite", |b| b.iter(|| {
+            row.copy_from_slice(&under);
+            simd::composite(isa, &mut row, black_box(&over), 0.5, false);
+        }));
+        group.bench_function("gradient", |b| b.iter(|| simd::gradient(isa, &mut row, black_box(&[255, 0, 17, 255]), &[3, 250, 128, 0], (0.0, 0.001))));
+        group.finish();
+    }
+}
+
+/// Gradients in sRGB are interpolated by the vector kernels, other color spaces pixel by pixel
+fn gradients(c: &mut Criterion) {
+    let background = |color_space| Background::Gradient {
+        start: Point2D::new_color(0.0, 0.0, Color::blue()),
+        end: Point2D::new_color(SIZE as f64, SIZE as f64, Color::rgba(255, 255, 255, 128)),
+        color_space
+    };
+    let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+    let srgb = background(ColorSpace::Srgb);
+    let oklab = background(ColorSpace::Oklab);
+    c.bench_function("sRGB gradient 1024x1024", |b| b.iter(|| black_box(&srgb).paint(&mut framebuffer)));
+    c.bench_function("Oklab gradient 1024x1024", |b| b.iter(|| black_box(&oklab).paint(&mut framebuffer)));
+}
+
+/// A solid Triangle is filled in spans, a gradient still shades every pixel
+fn fill_triangles(c: &mut Criterion) {
+    let triangle = |a: Color, b: Color, c: Color| Triangle2D::new(Point2D::new_color(0.0, 0.0, a),
+                                                                  Point2D::new_color(SIZE as f64, 0.0, b),
+                                                                  Point2D::new_color(0.0, SIZE as f64, c));
+    let solid = triangle(Color::red(), Color::red(), Color::red());
+    let gradient = triangle(Color::red(), Color::green(), Color::blue());
+    let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+    c.bench_function("solid triangle", |b| b.iter(|| black_box(&solid).draw(&mut framebuffer)));
+    c.bench_function("gradient triangle", |b| b.iter(|| black_box(&gradient).draw(&mut framebuffer)));
+}
+
+/// The Triangles of the commented example in `main.rs`, filled in spans and with the per-pixel path
+fn large_triangles(c: &mut Criterion) {
+    let size = LARGE as f64;
+    let solid = Triangle2D::new(Point2D::new_color(0.0, 0.0, Color::red()),
+                                Point2D::new_color(0.0, size, Color::red()),
+                                Point2D::new_color(size, 0.0, Color::red()));
+    let gradient = Triangle2D::new(Point2D::new_color(size, size, Color::red()),
+                                   Point2D::new_color(0.0, size, Color::blue()),
+                                   Point2D::new_color(size, 0.0, Color::green()));
+    let mut framebuffer = Framebuffer::new(LARGE, LARGE);
+    let mut group = c.benchmark_group("Triangle 10000x10000");
+    group.sample_size(10);
+    group.bench_function("solid spans", |b| b.iter(|| black_box(&solid).draw(&mut framebuffer)));
+    group.bench_function("solid get/blend/set", |b| b.iter(|| black_box(&solid).draw(&mut PerPixel(&mut framebuffer))));
+    group.bench_function("gradient", |b| b.iter(|| black_box(&gradient).draw(&mut framebuffer)));
+    group.finish();
+}
+
+criterion_group!(benches, blend_rows, composite, kernels, gradients, fill_triangles, large_triangles);
+criterion_main!(benches);
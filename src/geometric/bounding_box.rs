@@ -0,0 +1,139 @@
+use std;
+
+use geometric::Transform2D;
+
+/// Represents an axis-aligned 2D Rectangle enclosing a Geometric-Object
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    /// Smallest X-Coordinate inside the box
+    pub min_x: f64,
+    /// Smallest Y-Coordinate inside the box
+    pub min_y: f64,
+    /// Largest X-Coordinate inside the box
+    pub max_x: f64,
+    /// Largest Y-Coordinate inside the box
+    pub max_y: f64
+}
+
+impl BoundingBox {
+    /// Returns the box spanned by two corners in any order
+    ///
+    /// # Arguments
+    ///
+    /// * `x0` - X-Coordinate of the first corner
+    /// * `y0` - Y-Coordinate of the first corner
+    /// * `x1` - X-Coordinate of the opposite corner
+    /// * `y1` - Y-Coordinate of the opposite corner
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// use graphic_library::geometric::BoundingBox;
+    /// fn main(){
+    /// let bounds = BoundingBox::new(10.0, 0.0, 0.0, 5.0);
+    /// assert_eq!(10.0, bounds.width());
+    /// }
+    /// ```
+    pub fn new(x0: f64, y0: f64, x1: f64, y1: f64) -> BoundingBox {
+        BoundingBox {
+            min_x: x0.min(x1),
+            min_y: y0.min(y1),
+            max_x: x0.max(x1),
+            max_y: y0.max(y1)
+        }
+    }
+
+    /// Returns the smallest box containing all given coordinates or `None` if there are none
+    pub fn from_points<I: IntoIterator<Item = (f64, f64)>>(points: I) -> Option<BoundingBox> {
+        BoundingBox::union_all(points.into_iter().map(|(x, y)| BoundingBox::new(x, y, x, y)))
+    }
+
+    /// Returns the smallest box containing all given boxes or `None` if there are none
+    pub fn union_all<I: IntoIterator<Item = BoundingBox>>(boxes: I) -> Option<BoundingBox> {
+        boxes.into_iter().fold(None, |bounds, other| Some(bounds.map_or(other, |bounds: BoundingBox| bounds.union(&other))))
+    }
+
+    /// Returns the width of the box
+    pub fn width(&self) -> f64 {
+        self.max_x - self.min_x
+    }
+
+    /// Returns the height of the box
+    pub fn height(&self) -> f64 {
+        self.max_y - self.min_y
+    }
+
+    /// Returns the center of the box
+    pub fn center(&self) -> (f64, f64) {
+        ((self.min_x + self.max_x) / 2.0, (self.min_y + self.max_y) / 2.0)
+    }
+
+    /// Returns the smallest box containing both boxes
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        BoundingBox {
+            min_x: self.min_x.min(other.min_x),
+            min_y: self.min_y.min(other.min_y),
+            max_x: self.max_x.max(other.max_x),
+            max_y: self.max_y.max(other.max_y)
+        }
+    }
+
+    /// Returns whether both boxes overlap, touching edges count as overlap
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        self.min_x <= other.max_x && other.min_x <= self.max_x && self.min_y <= other.max_y && other.min_y <= self.max_y
+    }
+
+    /// Returns whether a coordinate lies inside the box or on its edge
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.min_x && x <= self.max_x && y >= self.min_y && y <= self.max_y
+    }
+
+    /// Returns the distance of a coordinate to the box, 0 inside of it
+    pub fn distance(&self, x: f64, y: f64) -> f64 {
+        let dx = (self.min_x - x).max(x - self.max_x).max(0.0);
+        let dy = (self.min_y - y).max(y - self.max_y).max(0.0);
+        dx.hypot(dy)
+    }
+
+    /// Returns the box grown by a margin on every side
+    pub fn expand(&self, margin: f64) -> BoundingBox {
+        BoundingBox::new(self.min_x - margin, self.min_y - margin, self.max_x + margin, self.max_y + margin)
+    }
+
+    /// Returns the box enclosing the transformed corners of the box
+    pub fn transform(&self, m: &Transform2D) -> BoundingBox {
+        BoundingBox::from_points(vec![m.apply(self.min_x, self.min_y),
+                                      m.apply(self.max_x, self.min_y),
+                                      m.apply(self.min_x, self.max_y),
+                                      m.apply(self.max_x, self.max_y)]).unwrap()
+    }
+}
+
+impl std::fmt::Display for BoundingBox {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "BoundingBox from ({}, {}) to ({}, {})", self.min_x, self.min_y, self.max_x, self.max_y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn union_and_intersects() {
+        let a = BoundingBox::new(0.0, 0.0, 2.0, 2.0);
+        let b = BoundingBox::new(3.0, 1.0, 4.0, 5.0);
+        assert!(!a.intersects(&b));
+        assert!(a.expand(1.0).intersects(&b));
+        assert_eq!(BoundingBox::new(0.0, 0.0, 4.0, 5.0), a.union(&b));
+        assert_eq!(None, BoundingBox::from_points(vec![]));
+    }
+
+    #[test]
+    fn transform_encloses_rotated_corners() {
+        let bounds = BoundingBox::new(0.0, 0.0, 2.0, 1.0).transform(&Transform2D::rotation(90.0));
+        assert!((bounds.min_x + 1.0).abs() < 1e-9 && bounds.max_x.abs() < 1e-9);
+        assert!(bounds.min_y.abs() < 1e-9 && (bounds.max_y - 2.0).abs() < 1e-9);
+    }
+}
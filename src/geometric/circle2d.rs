@@ -4,10 +4,15 @@ use std;
 
 use raster::Color;
 use geometric::Geometric2D;
-use geometric::Point2D;
+use geometric::{BoundingBox, Point2D, Transform2D};
+use graphic::{Canvas, CoverageRasterizer};
 
 /// Represents a 2D Circle
-#[derive(Debug)]
+///
+/// `draw` and `draw_aa` fill the Circle, `draw_outline` and `draw_outline_aa` trace a ring one pixel wide,
+/// like for every other Geometric-Object.
+/// Center and radius keep their sub-pixel precision, use `stroke` for thicker rings.
+#[derive(Debug, Clone)]
 pub struct Circle2D {
     /// Radius of the Circle
     pub r: f64,
@@ -34,11 +39,137 @@ impl Circle2D {
     /// }
     /// ```
     pub fn new(r: f64, m: Point2D) -> Circle2D {
-        Circle2D { r: r, m: m }
+        Circle2D { r, m }
     }
 
     /// Returns the color of the Circle
     pub fn get_color(&self) -> Color { self.m.get_color() }
+
+    /// Fills the Circle anti-aliased
+    ///
+    /// # Arguments
+    ///
+    /// * `canvas` - Canvas to draw into
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// use graphic_library::geometric::{Circle2D, Point2D};
+    /// use graphic_library::graphic::Framebuffer;
+    /// fn main(){
+    /// //Draws a dot of a scatter plot between the pixels
+    /// let mut canvas = Framebuffer::new(10, 10);
+    /// Circle2D::new(1.5, Point2D::new(4.25, 5.5)).fill(&mut canvas);
+    /// }
+    /// ```
+    pub fn fill(&self, canvas: &mut dyn Canvas) {
+        self.fill_ring(canvas, self.r.abs(), 0.0);
+    }
+
+    /// Draws the ring of the Circle anti-aliased, centered on the radius
+    ///
+    /// # Arguments
+    ///
+    /// * `canvas` - Canvas to draw into
+    /// * `thickness` - Width of the ring in pixels, rings wider than the diameter fill the Circle
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// use graphic_library::geometric::{Circle2D, Point2D};
+    /// use graphic_library::graphic::Framebuffer;
+    /// fn main(){
+    /// let mut canvas = Framebuffer::new(100, 100);
+    /// Circle2D::new(40.0, Point2D::new(50.0, 50.0)).stroke(&mut canvas, 2.5);
+    /// }
+    /// ```
+    pub fn stroke(&self, canvas: &mut dyn Canvas, thickness: f64) {
+        let half = thickness.abs() / 2.0;
+        self.fill_ring(canvas, self.r.abs() + half, self.r.abs() - half);
+    }
+
+    /// Fills the area between two radii anti-aliased
+    fn fill_ring(&self, canvas: &mut dyn Canvas, outer: f64, inner: f64) {
+        if outer <= 0.0 {
+            return;
+        }
+        let bounds = BoundingBox::new(self.m.x - outer, self.m.y - outer, self.m.x + outer, self.m.y + outer);
+        let mut rasterizer = CoverageRasterizer::for_canvas(canvas, &bounds);
+        rasterizer.polygon(&self.polygon(outer));
+        if inner > 0.0 {
+            //The hole winds the other way round and cancels the coverage
+            let mut hole = self.polygon(inner);
+            hole.reverse();
+            rasterizer.polygon(&hole);
+        }
+        rasterizer.fill(canvas, &self.get_color(), self.m.depth);
+    }
+
+    /// Paints the pixels whose centers lie between two radii
+    fn draw_ring(&self, canvas: &mut dyn Canvas, outer: f64, inner: f64) {
+        if outer <= 0.0 {
+            return;
+        }
+        let inner = inner.max(0.0);
+        let (clip_x0, clip_y0, clip_x1, clip_y1) = canvas.clip_rect();
+        let color = self.get_color();
+        let y0 = ((self.m.y - outer - 0.5).ceil() as i32).max(clip_y0);
+        let y1 = ((self.m.y + outer - 0.5).floor() as i32 + 1).min(clip_y1);
+        for y in y0..y1 {
+            let dy = y as f64 + 0.5 - self.m.y;
+            if dy * dy > outer * outer {
+                continue;
+            }
+            let half = (outer * outer - dy * dy).sqrt();
+            let x0 = ((self.m.x - half - 0.5).ceil() as i32).max(clip_x0);
+            let x1 = ((self.m.x + half - 0.5).floor() as i32 + 1).min(clip_x1);
+            if x0 >= x1 {
+                continue;
+            }
+            //Pixel centers within the inner radius form a hole splitting the row into two spans
+            let in_hole = |x: i32| {
+                let dx = x as f64 + 0.5 - self.m.x;
+                dx * dx + dy * dy < inner * inner
+            };
+            let hole = (inner * inner - dy * dy).max(0.0).sqrt();
+            let mut h0 = ((self.m.x - hole - 0.5).ceil() as i32).clamp(x0, x1);
+            let mut h1 = ((self.m.x + hole - 0.5).floor() as i32 + 1).clamp(h0, x1);
+            //Corrects the rounding of the square root at the borders of the hole
+            while h0 < h1 && !in_hole(h0) {
+                h0 += 1;
+            }
+            while h0 > x0 && in_hole(h0 - 1) {
+                h0 -= 1;
+            }
+            while h1 > h0 && !in_hole(h1 - 1) {
+                h1 -= 1;
+            }
+            while h1 < x1 && h1 > h0 && in_hole(h1) {
+                h1 += 1;
+            }
+            if h0 == h1 {
+                canvas.blend_span_depth(x0, x1, y, &color, 1.0, self.m.depth);
+            } else {
+                canvas.blend_span_depth(x0, h0, y, &color, 1.0, self.m.depth);
+                canvas.blend_span_depth(h1, x1, y, &color, 1.0, self.m.depth);
+            }
+        }
+    }
+
+    /// Returns a polygon enclosing the same area as a circle around the center, deviating less than 1/100 pixel from it
+    fn polygon(&self, radius: f64) -> Vec<(f64, f64)> {
+        let step = 2.0 * (1.0 - 0.01 / radius.max(0.01)).acos();
+        let n = ((2.0 * std::f64::consts::PI / step).ceil() as usize).max(8);
+        let angle = 2.0 * std::f64::consts::PI / n as f64;
+        //Pushes the corners outwards so the chords cut off as much as they add
+        let corner = radius * (angle / angle.sin()).sqrt();
+        (0..n).map(|i| {
+            let a = angle * i as f64;
+            (self.m.x + corner * a.cos(), self.m.y + corner * a.sin())
+        }).collect()
+    }
 }
 
 impl std::fmt::Display for Circle2D {
@@ -59,7 +190,7 @@ impl Geometric2D for Circle2D {
     #[allow(unused_variables)]
     //TODO make ellipsis if sx and sy not equals
     fn scale(&mut self, sx: f64, sy: f64) {
-        self.r = self.r * sx;
+        self.r *= sx;
     }
 
     fn rotate(&mut self, angle: f64) {
@@ -76,102 +207,94 @@ impl Geometric2D for Circle2D {
         self.r *= sx;
     }
 
-    fn draw(&self, canvas: &mut raster::Image) {
-        let xm: i32 = self.m.x as i32;
-        let ym: i32 = self.m.y as i32;
-        let mut radius: i32 = self.r as i32;
-        let mut x: i32 = -radius;
-        let mut y: i32 = 0;
-        let mut err: i32 = 2 - 2 * radius;
-        loop {
-            canvas.set_pixel(xm - x, ym - y, self.m.get_color().clone()).unwrap();/* I. Quadrant +x +y */
-            canvas.set_pixel(xm + x, ym - y, self.m.get_color().clone()).unwrap();/* II. Quadrant -x +y */
-            canvas.set_pixel(xm + x, ym + y, self.m.get_color().clone()).unwrap();/* III. Quadrant -x -y */
-            canvas.set_pixel(xm - x, ym + y, self.m.get_color().clone()).unwrap();/* IV. Quadrant +x -y */
-
-            radius = err;
-            if radius <= y {
-                /* e_xy+e_y < 0 */
-                y += 1;
-                err += y * 2 + 1;
-            }
-            if radius > x || err > y {
-                /* e_xy+e_x > 0 or no 2nd y-step */
-                x += 1;
-                err += x * 2 + 1;
-            }
+    //TODO make ellipsis if the Transformation does not scale uniformly
+    fn apply_transform(&mut self, m: &Transform2D) {
+        self.m.apply_transform(m);
+        self.r *= m.scale_factor();
+    }
 
-            if x > 0 { break; }
-        }
+    fn box_clone(&self) -> Box<dyn Geometric2D> {
+        Box::new(self.clone())
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        let r = self.r.abs();
+        Some(BoundingBox::new(self.m.x - r, self.m.y - r, self.m.x + r, self.m.y + r))
+    }
+
+    fn contains(&self, p: &Point2D) -> bool {
+        self.m.distance(p) <= self.r.abs()
     }
 
-    fn draw_outline(&self, canvas: &mut raster::Image) {
-        let r2 = (self.r * self.r) as i32;
-        let area = r2 << 2;
-        let rr = (self.r as i32) << 1;
-        let r = self.r as i32;
-        for i in 0..area {
-            let tx = (i % rr) - r;
-            let ty = (i / rr) - r;
-            if tx * tx + ty * ty <= r2 {
-                canvas.set_pixel(self.m.x as i32 + tx, self.m.y as i32 + ty, self.m.get_color()).unwrap();
+    fn distance(&self, p: &Point2D) -> f64 {
+        (self.m.distance(p) - self.r.abs()).max(0.0)
+    }
+
+    fn draw(&self, canvas: &mut dyn Canvas) {
+        self.draw_ring(canvas, self.r.abs(), 0.0);
+    }
+
+    fn draw_outline(&self, canvas: &mut dyn Canvas) {
+        //The outermost pixels of the fill
+        self.draw_ring(canvas, self.r.abs(), self.r.abs() - 1.0);
+    }
+
+    fn draw_aa(&self, canvas: &mut dyn Canvas) {
+        self.fill(canvas);
+    }
+
+    fn draw_outline_aa(&self, canvas: &mut dyn Canvas) {
+        self.stroke(canvas, 1.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphic::Framebuffer;
+
+    /// Returns the covered area of all pixels
+    fn painted_area(canvas: &Framebuffer) -> f64 {
+        let mut area = 0.0;
+        for y in 0..canvas.height() {
+            for x in 0..canvas.width() {
+                area += canvas.get_pixel(x, y).unwrap().r as f64 / 255.0;
             }
         }
+        area
     }
 
-    fn draw_aa(&self, canvas: &mut raster::Image) {
-        self.draw_outline_aa(canvas);
-        self.draw(canvas);
-    }
-
-    fn draw_outline_aa(&self, canvas: &mut raster::Image) {
-        let xm: i32 = self.m.x as i32;
-        let ym: i32 = self.m.y as i32;
-        let mut x: i32 = self.r as i32;
-        let mut y: i32 = 0; /* II. quadrant from bottom left to top right */
-        let mut i: i32;
-        let mut x2: i32;
-        let mut e2: i32;
-        let mut err: i32 = 2 - 2 * x; /* error of 1.step */
-        let r: i32 = 1 - err;
-
-        loop {
-            i = (255 * (err + 2 * (x + y) - 2).abs()) / r; /* get blend value of pixel */
-            let color = Color::rgba(self.get_color().r, self.get_color().g, self.get_color().b, i as u8);
-            canvas.set_pixel(xm + x, ym - y, color.clone()).unwrap(); /* I. Quadrant */
-            canvas.set_pixel(xm + y, ym + x, color.clone()).unwrap(); /* II. Quadrant */
-            canvas.set_pixel(xm - x, ym + y, color.clone()).unwrap(); /* III. Quadrant */
-            canvas.set_pixel(xm - y, ym - x, color.clone()).unwrap(); /* IV. Quadrant */
-            if x == 0 { break; }
-            e2 = err;
-            x2 = x; /* remember values */
-            if err > y {
-                /* x step */
-                i = (255 * (err + 2 * x - 1)) / r; /* outward pixel */
-                if i < 255 {
-                    let cc = Color::rgba(self.get_color().r, self.get_color().g, self.get_color().b, i as u8);
-                    canvas.set_pixel(xm + x, ym - y + 1, cc.clone()).unwrap();
-                    canvas.set_pixel(xm + y - 1, ym + x, cc.clone()).unwrap();
-                    canvas.set_pixel(xm - x, ym + y - 1, cc.clone()).unwrap();
-                    canvas.set_pixel(xm - y + 1, ym - x, cc.clone()).unwrap();
-                }
-                x -= 1;
-                err -= x * 2 - 1;
-            }
-            x2 -= 1;
-            if e2 <= x2 + 1 {
-                /* y step */
-                i = (255 * (1 - 2 * y - e2)) / r; /* inward pixel */
-                if i < 255 {
-                    let cc = Color::rgba(self.get_color().r, self.get_color().g, self.get_color().b, i as u8);
-                    canvas.set_pixel(xm + x2, ym - y, cc.clone()).unwrap();
-                    canvas.set_pixel(xm + y, ym + x2, cc.clone()).unwrap();
-                    canvas.set_pixel(xm - x2, ym + y, cc.clone()).unwrap();
-                    canvas.set_pixel(xm - y, ym - x2, cc.clone()).unwrap();
-                }
-                y -= 1;
-                err -= y * 2 - 1;
+    #[test]
+    fn fill_and_stroke_cover_their_area() {
+        let circle = Circle2D::new(6.3, Point2D::new_color(10.25, 9.6, Color::red()));
+        let mut canvas = Framebuffer::new(20, 20);
+        canvas.clear(&Color::black());
+        circle.fill(&mut canvas);
+        assert!((painted_area(&canvas) - std::f64::consts::PI * 6.3 * 6.3).abs() < 0.5);
+        canvas.clear(&Color::black());
+        circle.stroke(&mut canvas, 2.5);
+        assert!((painted_area(&canvas) - 2.0 * std::f64::consts::PI * 6.3 * 2.5).abs() < 0.5);
+        assert_eq!(0, canvas.get_pixel(10, 9).unwrap().r);
+    }
+
+    #[test]
+    fn negative_radius_is_its_magnitude() {
+        let circle = Circle2D::new(-3.0, Point2D::new(5.0, 5.0));
+        assert!(circle.contains(&Point2D::new(5.0, 5.0)));
+        assert_eq!(1.0, circle.distance(&Point2D::new(9.0, 5.0)));
+        assert_eq!(Some(BoundingBox::new(2.0, 2.0, 8.0, 8.0)), circle.bounding_box());
+    }
+
+    #[test]
+    fn draw_fills_pixel_centers_inside() {
+        let circle = Circle2D::new(4.7, Point2D::new_color(6.5, 5.2, Color::red()));
+        let mut canvas = Framebuffer::new(14, 12);
+        circle.draw(&mut canvas);
+        for y in 0..12 {
+            for x in 0..14 {
+                let p = Point2D::new(x as f64 + 0.5, y as f64 + 0.5);
+                assert_eq!(circle.contains(&p), canvas.get_pixel(x, y).unwrap().r == 255, "pixel {}, {}", x, y);
             }
         }
     }
-}
\ No newline at end of file
+}
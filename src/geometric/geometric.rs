@@ -1,9 +1,26 @@
-extern crate raster;
+use geometric::{BoundingBox, Point2D, Transform2D};
+use graphic::Canvas;
 
-use geometric::Point2D;
+/// Bound Geometric-Objects have to meet, `Send + Sync` with the `parallel` feature and nothing without
+///
+/// The Renderer shares Geometric-Objects between threads when it rasterizes in parallel.
+#[cfg(feature = "parallel")]
+pub trait ThreadSafe: Send + Sync {}
+
+#[cfg(feature = "parallel")]
+impl<T: Send + Sync + ?Sized> ThreadSafe for T {}
+
+/// Bound Geometric-Objects have to meet, `Send + Sync` with the `parallel` feature and nothing without
+///
+/// The Renderer shares Geometric-Objects between threads when it rasterizes in parallel.
+#[cfg(not(feature = "parallel"))]
+pub trait ThreadSafe {}
+
+#[cfg(not(feature = "parallel"))]
+impl<T: ?Sized> ThreadSafe for T {}
 
 /// Basic 2D Geometric Trait with standard functions
-pub trait Geometric2D {
+pub trait Geometric2D: ThreadSafe {
     /// Homogenizes the Coordinates of the given struct
     fn homogenize(&mut self);
 
@@ -52,37 +69,86 @@ pub trait Geometric2D {
     ///
     fn scale_from_point(&mut self, sx: f64, sy: f64, p: &Point2D);
 
-    /// Draws a Geometric-Object onto an Image
+    /// Applies an affine Transformation to a Geometric-Object
+    ///
+    /// # Arguments
+    ///
+    /// * `m` - Transformation to apply
+    ///
+    fn apply_transform(&mut self, m: &Transform2D);
+
+    /// Maps a Geometric-Object from world coordinates onto the pixels of an image
+    ///
+    /// Geometry is transformed like with `apply_transform`. Geometric-Objects addressing whole pixels keep
+    /// the pixel on the side of their coordinates the axes point to, so they stay inside when the view flips an axis.
+    ///
+    /// # Arguments
+    ///
+    /// * `view` - Transformation from world coordinates to pixels
+    ///
+    fn apply_view(&mut self, view: &Transform2D) {
+        self.apply_transform(view);
+    }
+
+    /// Returns a boxed copy of a Geometric-Object
+    fn box_clone(&self) -> Box<dyn Geometric2D>;
+
+    /// Returns the axis-aligned box enclosing the geometry, `None` if there is nothing to enclose
+    fn bounding_box(&self) -> Option<BoundingBox>;
+
+    /// Returns whether a Point lies inside the filled area or on the geometry of a Geometric-Object
+    ///
+    /// # Arguments
+    ///
+    /// * `p` - Point to test
+    ///
+    fn contains(&self, p: &Point2D) -> bool;
+
+    /// Returns the distance of a Point to the geometry of a Geometric-Object, 0 inside of filled areas
+    ///
+    /// # Arguments
+    ///
+    /// * `p` - Point to measure from
+    ///
+    fn distance(&self, p: &Point2D) -> f64;
+
+    /// Draws a Geometric-Object onto a Canvas
     ///
     /// # Arguments
     ///
     /// * `canvas` - Drawing Surface
     ///
-    fn draw(&self, canvas: &mut raster::Image);
+    fn draw(&self, canvas: &mut dyn Canvas);
 
-    /// Draws the outline of a Geometric-Object onto an Image
+    /// Draws the outline of a Geometric-Object onto a Canvas
     ///
     /// # Arguments
     ///
     /// * `canvas` - Drawing Surface
     ///
-    fn draw_outline(&self, canvas: &mut raster::Image);
+    fn draw_outline(&self, canvas: &mut dyn Canvas);
 
-    /// Draws an anti-aliased Geometric-Object onto an Image
+    /// Draws an anti-aliased Geometric-Object onto a Canvas
     ///
     /// # Arguments
     ///
     /// * `canvas` - Drawing Surface
     ///
-    fn draw_aa(&self, canvas: &mut raster::Image);
+    fn draw_aa(&self, canvas: &mut dyn Canvas);
 
-    /// Draws the anti-aliased outline of a Geometric-Object onto an Image
+    /// Draws the anti-aliased outline of a Geometric-Object onto a Canvas
     ///
     /// # Arguments
     ///
     /// * `canvas` - Drawing Surface
     ///
-    fn draw_outline_aa(&self, canvas: &mut raster::Image);
+    fn draw_outline_aa(&self, canvas: &mut dyn Canvas);
+}
+
+impl Clone for Box<dyn Geometric2D> {
+    fn clone(&self) -> Box<dyn Geometric2D> {
+        self.box_clone()
+    }
 }
 
 /// Linearly interpolates two values together
@@ -0,0 +1,299 @@
+extern crate raster;
+
+use std;
+
+use geometric::{BoundingBox, Geometric2D, Point2D, Transform2D};
+use graphic::Canvas;
+
+/// Represents a Group of Geometric-Objects sharing a Transformation, opacity and visibility
+///
+/// Transforming a Group only changes its Transformation, the geometry of the children stays untouched.
+/// Groups may contain other Groups, their Transformations are applied from the inside out.
+pub struct Group {
+    /// Children of the Group
+    children: Vec<Box<dyn Geometric2D>>,
+    /// Transformation from the coordinates of the children into the coordinates of the parent
+    transform: Transform2D,
+    /// Opacity between 0..1
+    opacity: f64,
+    /// Whether the Group is drawn at all
+    visible: bool
+}
+
+impl Group {
+    /// Returns an empty, visible and opaque Group
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// use graphic_library::geometric::{Group, Line2D, Circle2D, Point2D, Geometric2D};
+    /// fn main(){
+    /// //Creates a symbol and places it twice
+    /// let mut valve = Group::new();
+    /// valve.add(Circle2D::new(5.0, Point2D::new(0.0, 0.0)));
+    /// valve.add(Line2D::new(Point2D::new(-5.0, 0.0), Point2D::new(5.0, 0.0)));
+    /// let mut left = valve.clone();
+    /// left.transform(100.0, 100.0);
+    /// let mut right = valve.clone();
+    /// right.rotate(90.0);
+    /// right.transform(200.0, 100.0);
+    /// }
+    /// ```
+    pub fn new() -> Group {
+        Group {
+            children: Vec::new(),
+            transform: Transform2D::identity(),
+            opacity: 1.0,
+            visible: true
+        }
+    }
+
+    /// Adds a Geometric-Object on top of the other children
+    pub fn add<G: Geometric2D + 'static>(&mut self, geo: G) {
+        self.children.push(Box::new(geo));
+    }
+
+    /// Returns the children in drawing order, bottom first
+    pub fn children(&self) -> &[Box<dyn Geometric2D>] {
+        &self.children
+    }
+
+    /// Returns the children mutably in drawing order, bottom first
+    pub fn children_mut(&mut self) -> &mut Vec<Box<dyn Geometric2D>> {
+        &mut self.children
+    }
+
+    /// Returns the Transformation of the Group
+    pub fn get_transform(&self) -> Transform2D {
+        self.transform
+    }
+
+    /// Replaces the Transformation of the Group
+    pub fn set_transform(&mut self, transform: Transform2D) {
+        self.transform = transform;
+    }
+
+    /// Returns the opacity of the Group
+    pub fn get_opacity(&self) -> f64 {
+        self.opacity
+    }
+
+    /// Sets the opacity between 0..1 the children are composited with
+    ///
+    /// Translucent Groups are drawn into a layer storing colors like the Canvas,
+    /// the layer is depth tested against the Canvas before it is composited.
+    pub fn set_opacity(&mut self, opacity: f64) {
+        self.opacity = opacity.clamp(0.0, 1.0);
+    }
+
+    /// Returns whether the Group is drawn
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Shows or hides the Group
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    /// Returns copies of the children with the Transformation of the Group applied
+    pub fn transformed_children(&self) -> Vec<Box<dyn Geometric2D>> {
+        self.children.iter().map(|child| {
+            let mut child = child.box_clone();
+            child.apply_transform(&self.transform);
+            child
+        }).collect()
+    }
+
+    fn draw_with(&self, canvas: &mut dyn Canvas, draw: &dyn Fn(&dyn Geometric2D, &mut dyn Canvas)) {
+        if !self.visible || self.opacity <= 0.0 {
+            return;
+        }
+        let children = self.transformed_children();
+        if self.opacity >= 1.0 {
+            for child in &children {
+                draw(&**child, canvas);
+            }
+            return;
+        }
+        // Children are composited as a whole, so overlapping children do not shine through each other
+        canvas.draw_layer(self.opacity, &mut |layer| {
+            for child in &children {
+                draw(&**child, layer);
+            }
+        });
+    }
+}
+
+impl Default for Group {
+    fn default() -> Group {
+        Group::new()
+    }
+}
+
+impl Clone for Group {
+    fn clone(&self) -> Group {
+        Group {
+            children: self.children.clone(),
+            transform: self.transform,
+            opacity: self.opacity,
+            visible: self.visible
+        }
+    }
+}
+
+impl std::fmt::Display for Group {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Group of {} children, {}", self.children.len(), self.transform)
+    }
+}
+
+impl Geometric2D for Group {
+    fn homogenize(&mut self) {
+        for child in &mut self.children {
+            child.homogenize();
+        }
+    }
+
+    fn transform(&mut self, tx: f64, ty: f64) {
+        self.apply_transform(&Transform2D::translation(tx, ty));
+    }
+
+    fn scale(&mut self, sx: f64, sy: f64) {
+        self.apply_transform(&Transform2D::scaling(sx, sy));
+    }
+
+    fn rotate(&mut self, angle: f64) {
+        self.apply_transform(&Transform2D::rotation(angle));
+    }
+
+    fn rotate_from_point(&mut self, angle: f64, p: &Point2D) {
+        self.apply_transform(&Transform2D::rotation_from_point(angle, p));
+    }
+
+    fn scale_from_point(&mut self, sx: f64, sy: f64, p: &Point2D) {
+        self.apply_transform(&Transform2D::scaling_from_point(sx, sy, p));
+    }
+
+    fn apply_transform(&mut self, m: &Transform2D) {
+        self.transform = self.transform.then(m);
+    }
+
+    fn apply_view(&mut self, view: &Transform2D) {
+        //The children have to know about the view, so the Transformation of the Group is applied to them
+        let mut children = self.transformed_children();
+        for child in &mut children {
+            child.apply_view(view);
+        }
+        self.children = children;
+        self.transform = Transform2D::identity();
+    }
+
+    fn box_clone(&self) -> Box<dyn Geometric2D> {
+        Box::new(self.clone())
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        if !self.visible {
+            return None;
+        }
+        BoundingBox::union_all(self.transformed_children().iter().filter_map(|child| child.bounding_box()))
+    }
+
+    fn contains(&self, p: &Point2D) -> bool {
+        self.visible && self.transformed_children().iter().any(|child| child.contains(p))
+    }
+
+    fn distance(&self, p: &Point2D) -> f64 {
+        if !self.visible {
+            return f64::INFINITY;
+        }
+        self.transformed_children().iter().map(|child| child.distance(p)).fold(f64::INFINITY, f64::min)
+    }
+
+    fn draw(&self, canvas: &mut dyn Canvas) {
+        self.draw_with(canvas, &|geo, canvas| geo.draw(canvas));
+    }
+
+    fn draw_outline(&self, canvas: &mut dyn Canvas) {
+        self.draw_with(canvas, &|geo, canvas| geo.draw_outline(canvas));
+    }
+
+    fn draw_aa(&self, canvas: &mut dyn Canvas) {
+        self.draw_with(canvas, &|geo, canvas| geo.draw_aa(canvas));
+    }
+
+    fn draw_outline_aa(&self, canvas: &mut dyn Canvas) {
+        self.draw_with(canvas, &|geo, canvas| geo.draw_outline_aa(canvas));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use raster::Color;
+    use graphic::{Framebuffer, HdrBuffer, BlendMode, DepthBuffer, DepthCanvas};
+
+    fn red_point() -> Point2D {
+        Point2D::new_color(1.0, 1.0, Color::red())
+    }
+
+    #[test]
+    fn transforms_children_when_drawing() {
+        let mut group = Group::new();
+        group.add(red_point());
+        group.transform(2.0, 0.0);
+        let mut outer = Group::new();
+        outer.add(group);
+        outer.scale(2.0, 2.0);
+        let mut canvas = Framebuffer::new(8, 8);
+        outer.draw(&mut canvas);
+        assert_eq!(255, canvas.get_pixel(6, 2).unwrap().r);
+        assert_eq!(0, canvas.get_pixel(1, 1).unwrap().r);
+        assert_eq!(Some(BoundingBox::new(6.0, 2.0, 6.0, 2.0)), outer.bounding_box());
+    }
+
+    #[test]
+    fn invisible_and_translucent() {
+        let mut group = Group::new();
+        group.add(red_point());
+        group.add(red_point());
+        group.set_visible(false);
+        let mut canvas = Framebuffer::new(4, 4);
+        canvas.clear(&Color::black());
+        group.draw(&mut canvas);
+        assert_eq!(0, canvas.get_pixel(1, 1).unwrap().r);
+        assert_eq!(None, group.bounding_box());
+        group.set_visible(true);
+        group.set_opacity(0.5);
+        group.draw(&mut canvas);
+        assert_eq!(128, canvas.get_pixel(1, 1).unwrap().r);
+    }
+
+    #[test]
+    fn translucent_in_hdr_keeps_precision() {
+        let mut group = Group::new();
+        for _ in 0..4 {
+            group.add(red_point());
+        }
+        group.set_opacity(0.5);
+        let mut hdr = HdrBuffer::new(4, 4);
+        hdr.set_blend_mode(BlendMode::Additive);
+        group.draw(&mut hdr);
+        assert_eq!(2.0, hdr.get(1, 1).unwrap()[0]);
+    }
+
+    #[test]
+    fn translucent_is_depth_tested() {
+        let mut group = Group::new();
+        group.add(red_point());
+        group.set_opacity(0.5);
+        let mut canvas = Framebuffer::new(4, 4);
+        canvas.clear(&Color::black());
+        let mut depth = DepthBuffer::new(4, 4);
+        depth.set(1, 1, -1.0);
+        group.draw(&mut DepthCanvas::new(&mut canvas, &mut depth));
+        assert_eq!(0, canvas.get_pixel(1, 1).unwrap().r);
+    }
+}
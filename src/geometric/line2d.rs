@@ -1,16 +1,18 @@
 extern crate raster;
 
 use std;
-use geometric::{Point2D, Geometric2D, interpolate};
-use raster::Color;
+use geometric::{BoundingBox, Point2D, Geometric2D, Transform2D};
+use graphic::{Canvas, ColorSpace, interpolate_color};
 
 /// Represents a 2D Line
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Line2D {
     /// Start-Point of the Line
     from: Point2D,
     /// End-Point of the Line
-    to: Point2D
+    to: Point2D,
+    /// Color space the colors of the Points are interpolated in, `None` follows the Canvas
+    color_space: Option<ColorSpace>
 }
 
 impl Line2D {
@@ -33,24 +35,52 @@ impl Line2D {
     /// ```
     pub fn new(from: Point2D, to: Point2D) -> Line2D {
         Line2D {
-            from: from,
-            to: to
+            from,
+            to,
+            color_space: None
         }
     }
 
-    fn draw(&self, canvas: &mut raster::Image) {
-        let dx: i32 = (self.to.x - self.from.x).abs() as i32;
-        let dy: i32 = (self.to.y - self.from.y).abs() as i32;
+    /// Sets the color space the colors of both Points are interpolated in
+    ///
+    /// # Arguments
+    ///
+    /// * `color_space` - Color space to use, `None` interpolates in the color space the Canvas blends in
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// extern crate raster;
+    /// use raster::Color;
+    /// use graphic_library::geometric::{Line2D, Point2D};
+    /// use graphic_library::graphic::ColorSpace;
+    /// fn main(){
+    /// //Creates a perceptually uniform gradient from blue to yellow
+    /// let mut line = Line2D::new(Point2D::new_color(0.0, 0.0, Color::blue()), Point2D::new_color(50.0, 0.0, Color::rgb(255, 255, 0)));
+    /// line.set_color_space(Some(ColorSpace::Oklab));
+    /// }
+    /// ```
+    pub fn set_color_space(&mut self, color_space: Option<ColorSpace>) {
+        self.color_space = color_space;
+    }
 
-        let sgnx: i32 = (self.to.x - self.from.x).signum() as i32;
-        let sgny: i32 = (self.to.y - self.from.y).signum() as i32;
+    fn draw(&self, canvas: &mut dyn Canvas) {
+        //Pixels the Line passes through at its ends, like the fill of a Triangle a Line ending on a pixel border stops before it
+        let (x0, y0) = (end_pixel(self.from.x, self.to.x), end_pixel(self.from.y, self.to.y));
+        let (x1, y1) = (end_pixel(self.to.x, self.from.x), end_pixel(self.to.y, self.from.y));
+        let dx: i32 = (x1 - x0).abs();
+        let dy: i32 = (y1 - y0).abs();
+
+        let sgnx: i32 = (x1 - x0).signum();
+        let sgny: i32 = (y1 - y0).signum();
         //Parallel Case
         let mut ppx: i32 = 0;
         let mut ppy: i32 = 0;
 
         //Diagonal Case
         let ddx: i32 = sgnx;
-        let ddy: i32 = -sgny;
+        let ddy: i32 = sgny;
 
         let err_fast: f64;
         let err_slow: f64;
@@ -59,17 +89,42 @@ impl Line2D {
             err_fast = dy as f64;
             err_slow = dx as f64;
         } else {
-            ppy = -sgny;
+            ppy = sgny;
             err_fast = dx as f64;
             err_slow = dy as f64;
         }
-        let mut x: i32 = self.from.x as i32;
-        let mut y: i32 = (self.from.y as i32 - canvas.height as i32).abs();
+        let mut x: i32 = x0;
+        let mut y: i32 = y0;
         let mut err: f64 = err_slow / 2f64;
 
-        canvas.set_pixel(x, y, self.from.get_color()).unwrap();
+        let space = self.color_space.unwrap_or_else(|| ColorSpace::for_canvas(canvas));
+        let from_color = self.from.get_color();
+        let to_color = self.to.get_color();
+        canvas.blend_pixel_depth(x, y, &from_color, 1.0, self.from.depth);
         let c: i32 = err_slow as i32;
-        for _ in 0..c {
+        if c == 0 {
+            return;
+        }
+        //Only walks the steps that can reach the clip rect, resuming the error term in closed form
+        let (cx0, cy0, cx1, cy1) = canvas.clip_rect();
+        let slope = err_fast / err_slow;
+        let (first, last) = if dx > dy {
+            visible_steps(x as f64, sgnx as f64, y as f64, sgny as f64 * slope, (cx0, cx1), (cy0, cy1))
+        } else {
+            visible_steps(y as f64, sgny as f64, x as f64, sgnx as f64 * slope, (cy0, cy1), (cx0, cx1))
+        };
+        let first = first.max(1.0).ceil() as i32;
+        let last = last.min(c as f64).floor() as i32;
+        if first > last {
+            return;
+        }
+        //Diagonal steps taken before `first`, the error term of step `k` is err_slow / 2 - k * err_fast + diagonals * err_slow
+        let skipped = first as i64 - 1;
+        let diagonals = -(-(2 * skipped * err_fast as i64 - err_slow as i64)).div_euclid(2 * err_slow as i64);
+        err += diagonals as f64 * err_slow - skipped as f64 * err_fast;
+        x += diagonals as i32 * ddx + (skipped - diagonals) as i32 * ppx;
+        y += diagonals as i32 * ddy + (skipped - diagonals) as i32 * ppy;
+        for step in first..=last {
             err -= err_fast;
             //Diagonal Case
             if
@@ -82,58 +137,128 @@ impl Line2D {
                 x += ppx;
                 y += ppy;
             }
-            let dif = ((x as f64) - self.from.x).abs() / (dx as f64);
-            let r = interpolate(self.from.get_color().r as f64, self.to.get_color().r as f64, dif);
-            let g = interpolate(self.from.get_color().g as f64, self.to.get_color().g as f64, dif);
-            let b = interpolate(self.from.get_color().b as f64, self.to.get_color().b as f64, dif);
-            canvas.set_pixel(x, y, Color::rgb(r as u8, g as u8, b as u8)).unwrap();
-        }
-    }
-
-    fn draw_aa(&self, canvas: &mut raster::Image) {
-        let dx = (self.to.x - self.from.x).abs() as i32;
-        let sx = if self.from.x < self.to.x { 1 } else { -1 };
-        let dy = (self.to.y - self.from.y).abs() as i32;
-        let sy = if self.from.y < self.to.y { 1 } else { -1 };
-        let mut x2: i32;
-        let mut e2: i32;
-        let mut err: i32 = dx - dy; /* error value e_xy */
-        let ed: f32 = if dx + dy == 0 { 1.0f32 } else { ((dx * dx + dy * dy) as f32).sqrt() };
-        let ei: i32 = ed as i32;
-
-        let mut x0: i32 = self.from.x as i32;
-        let mut y0: i32 = self.from.y as i32;
-        let x1: i32 = self.to.x as i32;
-        let y1: i32 = self.to.y as i32;
-        loop {
-            let test = 1.0f32 - ((err - dx + dy).abs() as f32 / ed);
-            let color = self.from.get_color();
-            canvas.set_pixel(x0, y0, Color::rgb(((color.r as f32) * test) as u8, (color.g as f32 * test) as u8, (color.b as f32 * test) as u8)).unwrap();//TODO Add Color and mixing
-            e2 = err;
-            x2 = x0;
-
-            if 2 * e2 >= -dx {
-                if x0 == x1 { break; }
-                if e2 + dy < ei {
-                    let tt = 1.0f32 - (e2 + dy) as f32 / ed;
-                    canvas.set_pixel(x0, y0 + sy, Color::rgb((color.r as f32 * tt) as u8, (color.g as f32 * tt) as u8, (color.b as f32 * tt) as u8)).unwrap();
-                }
-                err -= dy;
-                x0 += sx;
+            let t = step as f64 / c as f64;
+            let color = interpolate_color(&from_color, &to_color, t, space);
+            let depth = self.from.depth + (self.to.depth - self.from.depth) * t;
+            canvas.blend_pixel_depth(x, y, &color, 1.0, depth);
+        }
+    }
+
+    /// Draws the Line anti-aliased with the algorithm of Xiaolin Wu
+    ///
+    /// Endpoints keep their sub-pixel position, the pixels at the ends are covered by the part of the Line reaching into them.
+    fn draw_aa(&self, canvas: &mut dyn Canvas) {
+        //Moves pixel centers onto integer coordinates
+        let (mut x0, mut y0) = (self.from.x - 0.5, self.from.y - 0.5);
+        let (mut x1, mut y1) = (self.to.x - 0.5, self.to.y - 0.5);
+        //Steps along the longer axis, one or two pixels per step across
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        if steep {
+            std::mem::swap(&mut x0, &mut y0);
+            std::mem::swap(&mut x1, &mut y1);
+        }
+        let reversed = x0 > x1;
+        if reversed {
+            std::mem::swap(&mut x0, &mut x1);
+            std::mem::swap(&mut y0, &mut y1);
+        }
+        let dx = x1 - x0;
+        let gradient = if dx == 0.0 { 0.0 } else { (y1 - y0) / dx };
+
+        let (cx0, cy0, cx1, cy1) = canvas.clip_rect();
+        let space = self.color_space.unwrap_or_else(|| ColorSpace::for_canvas(canvas));
+        let from_color = self.from.get_color();
+        let to_color = self.to.get_color();
+        let mut plot = |x: i64, y: f64, coverage: f64| {
+            if coverage <= 0.0 {
+                return;
             }
-            if 2 * e2 <= dy {
-                if y0 == y1 { break; }
-                if dx - e2 < ei {
-                    let base = 1.0f32 - ((dx - e2) as f32 / ed);
-                    canvas.set_pixel(x2 + sx, y0, Color::rgb((color.r as f32 * base) as u8, (color.g as f32 * base) as u8, (color.b as f32 * base) as u8)).unwrap();
+            //Position along the Line from `from` to `to`
+            let t = if dx == 0.0 { 0.0 } else { ((x as f64 - x0) / dx).clamp(0.0, 1.0) };
+            let t = if reversed { 1.0 - t } else { t };
+            let color = interpolate_color(&from_color, &to_color, t, space);
+            let depth = self.from.depth + (self.to.depth - self.from.depth) * t;
+            let below = y.floor();
+            let fract = y - below;
+            for &(across, share) in &[(below as i32, 1.0 - fract), (below as i32 + 1, fract)] {
+                if share > 0.0 {
+                    let (px, py) = if steep { (across, x as i32) } else { (x as i32, across) };
+                    canvas.blend_pixel_depth(px, py, &color, coverage * share, depth);
                 }
-                err += dx;
-                y0 += sy;
             }
+        };
+
+        let first = x0.round();
+        let last = x1.round();
+        if first == last {
+            //Both ends lie in the same pixel
+            plot(first as i64, y0 + gradient * (first - x0), dx);
+            return;
+        }
+        //Each end covers the part of its pixel the Line reaches into
+        plot(first as i64, y0 + gradient * (first - x0), first + 0.5 - x0);
+        plot(last as i64, y0 + gradient * (last - x0), x1 - (last - 0.5));
+        //Only walks the pixels that can reach the clip rect
+        let (major, minor) = if steep { ((cy0, cy1), (cx0, cx1)) } else { ((cx0, cx1), (cy0, cy1)) };
+        let (start, end) = visible_steps(0.0, 1.0, y0 - gradient * x0, gradient, major, minor);
+        let start = start.ceil().max(first + 1.0);
+        let end = (end.floor() + 1.0).min(last);
+        //Computed per pixel instead of accumulated, so clipped and whole Lines hit the same positions
+        for x in start as i64..end as i64 {
+            plot(x, y0 + gradient * (x as f64 - x0), 1.0);
         }
     }
 }
 
+/// Returns the pixel an end of a Line lies in, an end on a pixel border belongs to the pixel the Line goes on into
+///
+/// # Arguments
+///
+/// * `end` - Coordinate of the end
+/// * `other` - Coordinate of the other end on the same axis
+///
+fn end_pixel(end: f64, other: f64) -> i32 {
+    if other < end {
+        end.ceil() as i32 - 1
+    } else {
+        end.floor() as i32
+    }
+}
+
+/// Returns the range of steps along a Line that can paint inside a clip rect, empty if `first > last`
+///
+/// The range keeps a margin of a pixel, so rounding and the second pixel anti-aliasing paints across stay inside.
+///
+/// # Arguments
+///
+/// * `major` - Position on the axis stepped along at step 0
+/// * `major_step` - Change of `major` per step
+/// * `minor` - Position on the other axis at step 0
+/// * `minor_step` - Change of `minor` per step
+/// * `major_clip` - Clip rect on the axis stepped along, end excluded
+/// * `minor_clip` - Clip rect on the other axis, end excluded
+///
+fn visible_steps(major: f64, major_step: f64, minor: f64, minor_step: f64,
+                 major_clip: (i32, i32), minor_clip: (i32, i32)) -> (f64, f64) {
+    let range = |start: f64, step: f64, clip: (i32, i32)| {
+        let (lo, hi) = (clip.0 as f64 - 1.0 - start, clip.1 as f64 + 1.0 - start);
+        if step == 0.0 {
+            if lo <= 0.0 && 0.0 <= hi {
+                (f64::NEG_INFINITY, f64::INFINITY)
+            } else {
+                (f64::INFINITY, f64::NEG_INFINITY)
+            }
+        } else if step > 0.0 {
+            (lo / step, hi / step)
+        } else {
+            (hi / step, lo / step)
+        }
+    };
+    let (major_first, major_last) = range(major, major_step, major_clip);
+    let (minor_first, minor_last) = range(minor, minor_step, minor_clip);
+    (major_first.max(minor_first), major_last.min(minor_last))
+}
+
 impl std::fmt::Display for Line2D {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "Line from {} to {}", &self.from, &self.to)
@@ -141,11 +266,11 @@ impl std::fmt::Display for Line2D {
 }
 
 impl Geometric2D for Line2D {
-    fn draw(&self, canvas: &mut raster::Image) {
+    fn draw(&self, canvas: &mut dyn Canvas) {
         self.draw(canvas);
     }
 
-    fn draw_outline(&self, canvas: &mut raster::Image) {
+    fn draw_outline(&self, canvas: &mut dyn Canvas) {
         self.draw(canvas);
     }
 
@@ -179,11 +304,156 @@ impl Geometric2D for Line2D {
         self.to.scale_from_point(sx, sy, p);
     }
 
-    fn draw_aa(&self, canvas: &mut raster::Image) {
+    fn apply_transform(&mut self, m: &Transform2D) {
+        self.from.apply_transform(m);
+        self.to.apply_transform(m);
+    }
+
+    fn box_clone(&self) -> Box<dyn Geometric2D> {
+        Box::new(self.clone())
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        Some(BoundingBox::new(self.from.x, self.from.y, self.to.x, self.to.y))
+    }
+
+    fn contains(&self, p: &Point2D) -> bool {
+        self.distance(p) < 1e-9
+    }
+
+    fn distance(&self, p: &Point2D) -> f64 {
+        let dx = self.to.x - self.from.x;
+        let dy = self.to.y - self.from.y;
+        let length = dx * dx + dy * dy;
+        let t = if length == 0.0 {
+            0.0
+        } else {
+            (((p.x - self.from.x) * dx + (p.y - self.from.y) * dy) / length).clamp(0.0, 1.0)
+        };
+        (self.from.x + t * dx - p.x).hypot(self.from.y + t * dy - p.y)
+    }
+
+    fn draw_aa(&self, canvas: &mut dyn Canvas) {
         self.draw_aa(canvas);
     }
 
-    fn draw_outline_aa(&self, canvas: &mut raster::Image) {
+    fn draw_outline_aa(&self, canvas: &mut dyn Canvas) {
         self.draw_aa(canvas);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use raster::Color;
+    use graphic::Framebuffer;
+
+    fn red(canvas: &Framebuffer, x: i32, y: i32) -> u8 {
+        canvas.get_pixel(x, y).unwrap().r
+    }
+
+    #[test]
+    fn vertical_lines_interpolate_along_the_line() {
+        let mut line = Line2D::new(Point2D::new_color(2.5, 0.5, Color::black()), Point2D::new_color(2.5, 10.5, Color::red()));
+        line.set_color_space(Some(ColorSpace::Srgb));
+        let mut canvas = Framebuffer::new(4, 11);
+        line.draw(&mut canvas);
+        assert_eq!(0, red(&canvas, 2, 0));
+        assert_eq!(128, red(&canvas, 2, 5));
+        assert_eq!(255, red(&canvas, 2, 10));
+    }
+
+    #[test]
+    fn ends_on_pixel_borders_stay_inside_the_line() {
+        //Ends and the first and last pixel the Line passes through
+        let lines = [((2.0, 0.0), (2.0, 10.0), (2, 0), (2, 9)), ((2.0, 10.0), (2.0, 0.0), (2, 9), (2, 0)),
+                     ((0.0, 1.0), (6.0, 4.0), (0, 1), (5, 3)), ((6.0, 4.0), (0.0, 1.0), (5, 3), (0, 1))];
+        for &(from, to, first, last) in &lines {
+            let mut canvas = Framebuffer::new(12, 12);
+            Line2D::new(Point2D::new_color(from.0, from.1, Color::red()), Point2D::new_color(to.0, to.1, Color::red())).draw(&mut canvas);
+            let (min, max) = ((first.0.min(last.0), first.1.min(last.1)), (first.0.max(last.0), first.1.max(last.1)));
+            for y in 0..12 {
+                for x in 0..12 {
+                    let inside = x >= min.0 && x <= max.0 && y >= min.1 && y <= max.1;
+                    assert!(inside || red(&canvas, x, y) == 0, "{:?} to {:?} paints {} {}", from, to, x, y);
+                }
+            }
+            assert_eq!((255, 255), (red(&canvas, first.0, first.1), red(&canvas, last.0, last.1)));
+        }
+    }
+
+    #[test]
+    fn anti_aliased_ends_cover_fractional_pixels() {
+        let mut canvas = Framebuffer::new(8, 6);
+        canvas.clear(&Color::black());
+        //Runs along the border between the rows 2 and 3, from a quarter into pixel 1 to three quarters into pixel 5
+        Line2D::new(Point2D::new_color(1.25, 3.0, Color::red()), Point2D::new_color(5.75, 3.0, Color::red())).draw_aa(&mut canvas);
+        assert_eq!(0, red(&canvas, 0, 2));
+        assert_eq!(96, red(&canvas, 1, 2));
+        assert_eq!(128, red(&canvas, 3, 2));
+        assert_eq!(128, red(&canvas, 3, 3));
+        assert_eq!(96, red(&canvas, 5, 3));
+        assert_eq!(0, red(&canvas, 6, 3));
+        assert_eq!(0, red(&canvas, 3, 1));
+    }
+
+    /// Framebuffer only writing inside a clip rect and counting the blended pixels
+    struct Clipped {
+        framebuffer: Framebuffer,
+        clip: (i32, i32, i32, i32),
+        blends: usize
+    }
+
+    impl Canvas for Clipped {
+        fn width(&self) -> i32 {
+            self.framebuffer.width()
+        }
+
+        fn height(&self) -> i32 {
+            self.framebuffer.height()
+        }
+
+        fn get_pixel(&self, x: i32, y: i32) -> Option<Color> {
+            self.framebuffer.get_pixel(x, y)
+        }
+
+        fn set_pixel(&mut self, x: i32, y: i32, color: &Color) {
+            self.framebuffer.set_pixel(x, y, color);
+        }
+
+        fn blend_pixel(&mut self, x: i32, y: i32, color: &Color, coverage: f64) {
+            self.blends += 1;
+            let (x0, y0, x1, y1) = self.clip;
+            if x >= x0 && y >= y0 && x < x1 && y < y1 {
+                self.framebuffer.blend_pixel(x, y, color, coverage);
+            }
+        }
+
+        fn clip_rect(&self) -> (i32, i32, i32, i32) {
+            self.clip
+        }
+    }
+
+    #[test]
+    fn only_steps_through_the_clip_rect() {
+        let lines = [((-500.0, -300.0), (700.0, 500.0)), ((650.25, 3.5), (-400.75, 190.0)),
+                     ((85.5, -600.0), (100.0, 800.0)), ((300.0, 900.0), (-100.0, -700.25)), ((-900.0, 100.5), (900.0, 100.5))];
+        for &((fx, fy), (tx, ty)) in &lines {
+            let line = Line2D::new(Point2D::new_color(fx, fy, Color::red()), Point2D::new_color(tx, ty, Color::blue()));
+            let draws: [fn(&Line2D, &mut dyn Canvas); 2] = [|line, canvas| line.draw(canvas), |line, canvas| line.draw_aa(canvas)];
+            for draw in &draws {
+                let mut full = Framebuffer::new(200, 200);
+                draw(&line, &mut full);
+                let mut clipped = Clipped { framebuffer: Framebuffer::new(200, 200), clip: (60, 70, 110, 130), blends: 0 };
+                draw(&line, &mut clipped);
+                assert!(clipped.blends < 250, "{} blends for {}", clipped.blends, line);
+                for y in 70..130 {
+                    for x in 60..110 {
+                        let (a, b) = (full.get_pixel(x, y).unwrap(), clipped.framebuffer.get_pixel(x, y).unwrap());
+                        assert_eq!((a.r, a.g, a.b, a.a), (b.r, b.g, b.b, b.a), "{} at {} {}", line, x, y);
+                    }
+                }
+            }
+        }
+    }
+}
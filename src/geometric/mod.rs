@@ -1,13 +1,20 @@
 pub use self::point2d::Point2D;
 pub use self::line2d::Line2D;
-pub use self::geometric::Geometric2D;
+pub use self::geometric::{Geometric2D, ThreadSafe};
 pub use self::geometric::interpolate;
 pub use self::geometric::interpolate_barycentric;
 pub use self::triangle2d::Triangle2D;
 pub use self::circle2d::Circle2D;
+pub use self::transform2d::Transform2D;
+pub use self::group::Group;
+pub use self::bounding_box::BoundingBox;
 
 mod point2d;
 mod line2d;
+#[allow(clippy::module_inception)]
 mod geometric;
 mod triangle2d;
-mod circle2d;
\ No newline at end of file
+mod circle2d;
+mod transform2d;
+mod group;
+mod bounding_box;
\ No newline at end of file
@@ -5,6 +5,8 @@ use std::f64::consts::PI;
 
 use raster::Color;
 use geometric::geometric::Geometric2D;
+use geometric::{BoundingBox, Transform2D};
+use graphic::Canvas;
 
 /// Represents a 2D Point
 #[derive(Debug)]
@@ -15,6 +17,8 @@ pub struct Point2D {
     pub y: f64,
     /// Z-Coordinate of the Point
     pub z: f64,
+    /// Depth of the Point for depth testing, smaller values are closer to the viewer
+    pub depth: f64,
     /// Color of the Point
     color: Color
 }
@@ -110,13 +114,31 @@ impl Point2D {
     /// ```
     pub fn new_color_inhomogenized(x: f64, y: f64, z: f64, color: Color) -> Point2D {
         Point2D {
-            x: x,
-            y: y,
-            z: z,
-            color: color
+            x,
+            y,
+            z,
+            depth: 0.0,
+            color
         }
     }
 
+    /// Returns the Point with the given depth, smaller values are closer to the viewer
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// use graphic_library::geometric::{Point2D};
+    /// fn main(){
+    /// //Creates a white Point in front of Points with depth 0
+    /// let point = Point2D::new(5.0, 5.0).with_depth(-1.0);
+    /// }
+    /// ```
+    pub fn with_depth(mut self, depth: f64) -> Point2D {
+        self.depth = depth;
+        self
+    }
+
     /// Returns the color of the Point
     pub fn get_color(&self) -> Color { self.color.clone() }
 }
@@ -130,10 +152,11 @@ impl std::fmt::Display for Point2D {
 impl Clone for Point2D {
     fn clone(&self) -> Point2D {
         Point2D {
-            x: self.x.clone(),
-            y: self.y.clone(),
-            z: self.z.clone(),
-            color: Color::rgba(self.color.r.clone(), self.color.g.clone(), self.color.b.clone(), self.color.a.clone())
+            x: self.x,
+            y: self.y,
+            z: self.z,
+            depth: self.depth,
+            color: self.color.clone()
         }
     }
 }
@@ -175,19 +198,54 @@ impl Geometric2D for Point2D {
         self.transform(p.x, p.y);
     }
 
-    fn draw(&self, canvas: &mut raster::Image) {
-        canvas.set_pixel(self.x as i32, self.y as i32, self.color.clone()).unwrap();
+    fn apply_transform(&mut self, m: &Transform2D) {
+        let (x, y) = m.apply(self.x, self.y);
+        self.x = x;
+        self.y = y;
+    }
+
+    fn apply_view(&mut self, view: &Transform2D) {
+        //The pixel of the Point lies towards growing coordinates, an axis pointing the other way
+        //after the view puts the Point on the far border of its pixel
+        let (inside_x, inside_y) = view.apply(self.x + 0.5, self.y + 0.5);
+        self.apply_transform(view);
+        if inside_x < self.x {
+            self.x = self.x.ceil() - 1.0;
+        }
+        if inside_y < self.y {
+            self.y = self.y.ceil() - 1.0;
+        }
+    }
+
+    fn box_clone(&self) -> Box<dyn Geometric2D> {
+        Box::new(self.clone())
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        Some(BoundingBox::new(self.x, self.y, self.x, self.y))
+    }
+
+    fn contains(&self, p: &Point2D) -> bool {
+        self.x == p.x && self.y == p.y
+    }
+
+    fn distance(&self, p: &Point2D) -> f64 {
+        (self.x - p.x).hypot(self.y - p.y)
+    }
+
+    fn draw(&self, canvas: &mut dyn Canvas) {
+        canvas.blend_pixel_depth(self.x as i32, self.y as i32, &self.color, 1.0, self.depth);
     }
 
-    fn draw_outline(&self, canvas: &mut raster::Image) {
-        canvas.set_pixel(self.x as i32, self.y as i32, self.color.clone()).unwrap();
+    fn draw_outline(&self, canvas: &mut dyn Canvas) {
+        canvas.blend_pixel_depth(self.x as i32, self.y as i32, &self.color, 1.0, self.depth);
     }
-    fn draw_aa(&self, canvas: &mut raster::Image) {
-        canvas.set_pixel(self.x as i32, self.y as i32, self.color.clone()).unwrap();
+    fn draw_aa(&self, canvas: &mut dyn Canvas) {
+        canvas.blend_pixel_depth(self.x as i32, self.y as i32, &self.color, 1.0, self.depth);
     }
 
-    fn draw_outline_aa(&self, canvas: &mut raster::Image) {
-        canvas.set_pixel(self.x as i32, self.y as i32, self.color.clone()).unwrap();
+    fn draw_outline_aa(&self, canvas: &mut dyn Canvas) {
+        canvas.blend_pixel_depth(self.x as i32, self.y as i32, &self.color, 1.0, self.depth);
     }
 }
 
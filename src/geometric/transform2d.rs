@@ -0,0 +1,188 @@
+use std;
+use std::f64::consts::PI;
+
+use geometric::Point2D;
+
+/// Represents a 2D affine Transformation as the 3x3 matrix
+///
+/// ```text
+/// | a c e |
+/// | b d f |
+/// | 0 0 1 |
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform2D {
+    /// Scales and rotates x into x
+    pub a: f64,
+    /// Shears and rotates x into y
+    pub b: f64,
+    /// Shears and rotates y into x
+    pub c: f64,
+    /// Scales and rotates y into y
+    pub d: f64,
+    /// Translation on the x-axis
+    pub e: f64,
+    /// Translation on the y-axis
+    pub f: f64
+}
+
+impl Transform2D {
+    /// Returns the Transformation which leaves every Point in place
+    pub fn identity() -> Transform2D {
+        Transform2D { a: 1.0, b: 0.0, c: 0.0, d: 1.0, e: 0.0, f: 0.0 }
+    }
+
+    /// Returns a Translation
+    ///
+    /// # Arguments
+    ///
+    /// * `tx` - Translation on the x-axis
+    /// * `ty` - Translation on the y-axis
+    ///
+    pub fn translation(tx: f64, ty: f64) -> Transform2D {
+        Transform2D { a: 1.0, b: 0.0, c: 0.0, d: 1.0, e: tx, f: ty }
+    }
+
+    /// Returns a Scaling from the coordinate origin (0,0)
+    ///
+    /// # Arguments
+    ///
+    /// * `sx` - Scales on the x-axis
+    /// * `sy` - Scales on the y-axis
+    ///
+    pub fn scaling(sx: f64, sy: f64) -> Transform2D {
+        Transform2D { a: sx, b: 0.0, c: 0.0, d: sy, e: 0.0, f: 0.0 }
+    }
+
+    /// Returns a Rotation from the coordinate origin (0,0)
+    ///
+    /// # Arguments
+    ///
+    /// * `angle` - Angle in degrees, positive values rotate counter-clock vice
+    ///
+    pub fn rotation(angle: f64) -> Transform2D {
+        let a = angle * PI / 180.0;
+        Transform2D { a: a.cos(), b: a.sin(), c: -a.sin(), d: a.cos(), e: 0.0, f: 0.0 }
+    }
+
+    /// Returns a Rotation from a specified point as origin
+    ///
+    /// # Arguments
+    ///
+    /// * `angle` - Angle in degrees, positive values rotate counter-clock vice
+    /// * `p` - Point of origin to rotate from
+    ///
+    pub fn rotation_from_point(angle: f64, p: &Point2D) -> Transform2D {
+        Transform2D::translation(-p.x, -p.y).then(&Transform2D::rotation(angle)).then(&Transform2D::translation(p.x, p.y))
+    }
+
+    /// Returns a Scaling from a specified point as origin
+    ///
+    /// # Arguments
+    ///
+    /// * `sx` - Scales on the x-axis
+    /// * `sy` - Scales on the y-axis
+    /// * `p` - Point of origin to scale from
+    ///
+    pub fn scaling_from_point(sx: f64, sy: f64, p: &Point2D) -> Transform2D {
+        Transform2D::translation(-p.x, -p.y).then(&Transform2D::scaling(sx, sy)).then(&Transform2D::translation(p.x, p.y))
+    }
+
+    /// Returns the Transformation applying `self` first and `next` afterwards
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// use graphic_library::geometric::Transform2D;
+    /// fn main() {
+    /// //Scales by 2 and moves 10 to the right afterwards
+    /// let m = Transform2D::scaling(2.0, 2.0).then(&Transform2D::translation(10.0, 0.0));
+    /// assert_eq!((12.0, 2.0), m.apply(1.0, 1.0));
+    /// }
+    /// ```
+    pub fn then(&self, next: &Transform2D) -> Transform2D {
+        Transform2D {
+            a: next.a * self.a + next.c * self.b,
+            b: next.b * self.a + next.d * self.b,
+            c: next.a * self.c + next.c * self.d,
+            d: next.b * self.c + next.d * self.d,
+            e: next.a * self.e + next.c * self.f + next.e,
+            f: next.b * self.e + next.d * self.f + next.f
+        }
+    }
+
+    /// Returns the Transformation undoing `self` or `None` if it collapses the plane
+    pub fn inverse(&self) -> Option<Transform2D> {
+        let det = self.determinant();
+        if det.abs() < 1e-12 {
+            return None;
+        }
+        Some(Transform2D {
+            a: self.d / det,
+            b: -self.b / det,
+            c: -self.c / det,
+            d: self.a / det,
+            e: (self.c * self.f - self.d * self.e) / det,
+            f: (self.b * self.e - self.a * self.f) / det
+        })
+    }
+
+    /// Returns the factor by which areas are scaled, negative if the Transformation mirrors
+    pub fn determinant(&self) -> f64 {
+        self.a * self.d - self.b * self.c
+    }
+
+    /// Returns the factor by which lengths along the x-axis are scaled
+    pub fn scale_factor(&self) -> f64 {
+        (self.a * self.a + self.b * self.b).sqrt()
+    }
+
+    /// Returns the transformed coordinates
+    pub fn apply(&self, x: f64, y: f64) -> (f64, f64) {
+        (self.a * x + self.c * y + self.e, self.b * x + self.d * y + self.f)
+    }
+}
+
+impl Default for Transform2D {
+    fn default() -> Transform2D {
+        Transform2D::identity()
+    }
+}
+
+impl std::fmt::Display for Transform2D {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Transform [{} {} {}; {} {} {}]", self.a, self.c, self.e, self.b, self.d, self.f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geometric::Geometric2D;
+
+    fn assert_close(expected: (f64, f64), actual: (f64, f64)) {
+        assert!((expected.0 - actual.0).abs() < 0.00001 && (expected.1 - actual.1).abs() < 0.00001, "{:?} != {:?}", expected, actual);
+    }
+
+    #[test]
+    fn matches_point_operations() {
+        let center = Point2D::new(1.0, 2.0);
+        let mut point = Point2D::new(4.0, 3.0);
+        point.rotate_from_point(30.0, &center);
+        point.scale_from_point(2.0, 0.5, &center);
+        point.transform(-3.0, 7.0);
+        let m = Transform2D::rotation_from_point(30.0, &center)
+            .then(&Transform2D::scaling_from_point(2.0, 0.5, &center))
+            .then(&Transform2D::translation(-3.0, 7.0));
+        assert_close((point.x, point.y), m.apply(4.0, 3.0));
+    }
+
+    #[test]
+    fn inverse() {
+        let m = Transform2D::rotation(42.0).then(&Transform2D::scaling(2.0, 3.0)).then(&Transform2D::translation(5.0, -1.0));
+        let (x, y) = m.apply(7.0, 11.0);
+        assert_close((7.0, 11.0), m.inverse().unwrap().apply(x, y));
+        assert!(Transform2D::scaling(0.0, 1.0).inverse().is_none());
+    }
+}
@@ -1,18 +1,21 @@
 extern crate raster;
 
 use std;
-use geometric::{Geometric2D, Point2D, Line2D, interpolate_barycentric};
 use raster::Color;
+use geometric::{BoundingBox, Geometric2D, Point2D, Line2D, Transform2D};
+use graphic::{Canvas, ColorSpace, CoverageRasterizer, interpolate_color_barycentric};
 
 /// Represents a 2D Triangle
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Triangle2D {
     /// Point of Triangle
     a: Point2D,
     /// Point of Triangle
     b: Point2D,
     /// Point of Triangle
-    c: Point2D
+    c: Point2D,
+    /// Color space the colors of the Points are interpolated in, `None` follows the Canvas
+    color_space: Option<ColorSpace>
 }
 
 impl Triangle2D {
@@ -36,13 +39,252 @@ impl Triangle2D {
     /// ```
     pub fn new(a: Point2D, b: Point2D, c: Point2D) -> Triangle2D {
         Triangle2D {
-            a: a,
-            b: b,
-            c: c
+            a,
+            b,
+            c,
+            color_space: None
+        }
+    }
+
+    /// Sets the color space the colors of the Points are interpolated in
+    ///
+    /// # Arguments
+    ///
+    /// * `color_space` - Color space to use, `None` interpolates in the color space the Canvas blends in
+    ///
+    pub fn set_color_space(&mut self, color_space: Option<ColorSpace>) {
+        self.color_space = color_space;
+    }
+
+    /// Returns the edge functions of the Triangle or `None` if it covers no area at sub-pixel precision
+    fn edge_functions(&self) -> Option<EdgeFunctions> {
+        let (a, b, c) = (FixedPoint::from(&self.a), FixedPoint::from(&self.b), FixedPoint::from(&self.c));
+        let area = Edge::new(a, b).eval(c);
+        if area == 0 {
+            return None;
+        }
+        //Orients the Points clockwise on screen, so the inside is positive for every edge
+        let flipped = area < 0;
+        let (b, c) = if flipped { (c, b) } else { (b, c) };
+        Some(EdgeFunctions {
+            e: [Edge::new(b, c), Edge::new(c, a), Edge::new(a, b)],
+            area: area.abs(),
+            flipped
+        })
+    }
+
+    /// Returns the barycentric coordinates of (x,y), clamped into the Triangle
+    fn barycentric(&self, x: f64, y: f64) -> (f64, f64, f64) {
+        let area = (self.b.x - self.a.x) * (self.c.y - self.a.y) - (self.c.x - self.a.x) * (self.b.y - self.a.y);
+        if area == 0.0 {
+            return (1.0, 0.0, 0.0);
+        }
+        //Pixel centers of edge pixels may lie outside, their weights are clamped to the nearest edge
+        let beta = (((self.c.x - self.a.x) * (self.a.y - y) - (self.a.x - x) * (self.c.y - self.a.y)) / area).max(0.0);
+        let gamma = (((self.a.x - x) * (self.b.y - self.a.y) - (self.b.x - self.a.x) * (self.a.y - y)) / area).max(0.0);
+        let alpha = (1.0 - beta - gamma).max(0.0);
+        let sum = alpha + beta + gamma;
+        (alpha / sum, beta / sum, gamma / sum)
+    }
+
+    /// Returns the three edges of the Triangle
+    fn edges(&self) -> [Line2D; 3] {
+        let mut edges = [Line2D::new(self.a.clone(), self.b.clone()),
+                         Line2D::new(self.b.clone(), self.c.clone()),
+                         Line2D::new(self.c.clone(), self.a.clone())];
+        for edge in edges.iter_mut() {
+            edge.set_color_space(self.color_space);
+        }
+        edges
+    }
+
+    /// Paints the pixels whose centers lie inside the Triangle, or only those of them bordering on pixels outside
+    fn draw_spans(&self, canvas: &mut dyn Canvas, outline: bool) {
+        let edges = match self.edge_functions() {
+            Some(edges) => edges,
+            None => return
+        };
+        let (b, c) = if edges.flipped { (&self.c, &self.b) } else { (&self.b, &self.c) };
+        let color_a = self.a.get_color();
+        let color_b = b.get_color();
+        let color_c = c.get_color();
+        let (clip_x0, clip_y0, clip_x1, clip_y1) = canvas.clip_rect();
+        //Spans are computed for the whole width, so the outline does not follow the borders of the clip rect
+        let x_min = self.a.x.min(b.x.min(c.x)).floor() as i32;
+        let x_max = self.a.x.max(b.x.max(c.x)).ceil() as i32;
+        let y_min = (self.a.y.min(b.y.min(c.y)).floor() as i32).max(clip_y0);
+        let y_max = (self.a.y.max(b.y.max(c.y)).ceil() as i32).min(clip_y1);
+        if x_min.max(clip_x0) >= x_max.min(clip_x1) || y_min >= y_max {
+            return;
+        }
+        let space = self.color_space.unwrap_or_else(|| ColorSpace::for_canvas(canvas));
+        let area = edges.area as f64;
+        //A single color and depth is filled in spans instead of pixel by pixel
+        let same_color = |x: &Color, y: &Color| (x.r, x.g, x.b, x.a) == (y.r, y.g, y.b, y.a);
+        let solid = same_color(&color_a, &color_b) && same_color(&color_a, &color_c)
+            && self.a.depth == b.depth && self.a.depth == c.depth;
+        let paint = |canvas: &mut dyn Canvas, x0: i32, x1: i32, ys: i32| {
+            let (x0, x1) = (x0.max(clip_x0), x1.min(clip_x1));
+            if x0 >= x1 {
+                return;
+            }
+            if solid {
+                canvas.blend_span_depth(x0, x1, ys, &color_a, 1.0, self.a.depth);
+                return;
+            }
+            let start = FixedPoint::center(x0, ys);
+            let mut w = [edges.e[0].eval(start), edges.e[1].eval(start), edges.e[2].eval(start)];
+            for xs in x0..x1 {
+                let alpha = w[0] as f64 / area;
+                let beta = w[1] as f64 / area;
+                let gamma = w[2] as f64 / area;
+                let depth = alpha * self.a.depth + beta * b.depth + gamma * c.depth;
+                if canvas.depth_test(xs, ys, depth) {
+                    let color = interpolate_color_barycentric(&color_a, &color_b, &color_c, alpha, beta, gamma, space);
+                    canvas.blend_pixel(xs, ys, &color, 1.0);
+                }
+                for (w, edge) in w.iter_mut().zip(&edges.e) {
+                    *w += edge.a * ONE;
+                }
+            }
+        };
+        //Steps the edge functions from pixel center to pixel center instead of evaluating them anew,
+        //starting a row early so the outline knows the span above the first row
+        let start = FixedPoint::center(x_min, y_min - 1);
+        let mut row = [edges.e[0].eval(start), edges.e[1].eval(start), edges.e[2].eval(start)];
+        let mut next_span = || {
+            let span = edges.span(&row, x_min, x_max);
+            for (row, edge) in row.iter_mut().zip(&edges.e) {
+                *row += edge.b * ONE;
+            }
+            span
+        };
+        let mut above = next_span();
+        let mut span = next_span();
+        for ys in y_min..y_max {
+            let below = next_span();
+            let (x0, x1) = span;
+            if !outline {
+                paint(canvas, x0, x1, ys);
+            } else {
+                //Pixels inside the rows above and below and away from the ends of the span are interior
+                let inner0 = (x0 + 1).max(above.0).max(below.0);
+                let inner1 = (x1 - 1).min(above.1).min(below.1);
+                if inner0 >= inner1 {
+                    paint(canvas, x0, x1, ys);
+                } else {
+                    paint(canvas, x0, inner0, ys);
+                    paint(canvas, inner1, x1, ys);
+                }
+            }
+            above = span;
+            span = below;
         }
     }
 }
 
+/// Bits of sub-pixel precision, Points are snapped to 1/256 of a pixel
+const SUBPIXEL_BITS: u32 = 8;
+/// One pixel in fixed point
+const ONE: i64 = 1 << SUBPIXEL_BITS;
+
+/// Coordinates snapped to the sub-pixel grid
+#[derive(Clone, Copy)]
+struct FixedPoint {
+    x: i64,
+    y: i64
+}
+
+impl FixedPoint {
+    fn from(p: &Point2D) -> FixedPoint {
+        FixedPoint::new(p.x, p.y)
+    }
+
+    fn new(x: f64, y: f64) -> FixedPoint {
+        FixedPoint {
+            x: (x * ONE as f64).round() as i64,
+            y: (y * ONE as f64).round() as i64
+        }
+    }
+
+    /// Returns the center of a pixel
+    fn center(x: i32, y: i32) -> FixedPoint {
+        FixedPoint {
+            x: x as i64 * ONE + ONE / 2,
+            y: y as i64 * ONE + ONE / 2
+        }
+    }
+}
+
+/// Edge function `a*x + b*y + c` of a directed edge, positive right of the edge on screen
+struct Edge {
+    a: i64,
+    b: i64,
+    c: i64,
+    /// -1 for edges that must not paint pixels lying exactly on them
+    bias: i64
+}
+
+impl Edge {
+    fn new(from: FixedPoint, to: FixedPoint) -> Edge {
+        let dx = to.x - from.x;
+        let dy = to.y - from.y;
+        //Top-left fill rule: pixel centers on an edge belong to the triangle below or right of it
+        let top_left = (dy == 0 && dx > 0) || dy < 0;
+        Edge {
+            a: -dy,
+            b: dx,
+            c: dy * from.x - dx * from.y,
+            bias: if top_left { 0 } else { -1 }
+        }
+    }
+
+    fn eval(&self, p: FixedPoint) -> i64 {
+        self.a * p.x + self.b * p.y + self.c
+    }
+}
+
+/// Edge functions of a Triangle, each is positive on the inner side of the edge opposite to its Point
+struct EdgeFunctions {
+    /// Edges opposite to the Points a, b and c
+    e: [Edge; 3],
+    /// Twice the area of the Triangle in fixed point
+    area: i64,
+    /// Whether b and c were swapped to orient the edges
+    flipped: bool
+}
+
+impl EdgeFunctions {
+    fn inside(&self, p: FixedPoint) -> bool {
+        self.e.iter().all(|edge| edge.eval(p) + edge.bias >= 0)
+    }
+
+    /// Returns the pixels `x0..x1` of a row inside all edges
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - Edge functions at the center of pixel `x_min`
+    /// * `x_min` - First pixel of the row to consider
+    /// * `x_max` - Pixel after the last one to consider
+    ///
+    fn span(&self, row: &[i64; 3], x_min: i32, x_max: i32) -> (i32, i32) {
+        let (mut first, mut end) = (0, (x_max - x_min) as i64);
+        for (w, edge) in row.iter().zip(&self.e) {
+            //Solves w + bias + step * k >= 0 for the pixels k steps right of x_min
+            let w = w + edge.bias;
+            let step = edge.a * ONE;
+            if step > 0 {
+                first = first.max(-w.div_euclid(step));
+            } else if step < 0 {
+                end = end.min(w.div_euclid(-step) + 1);
+            } else if w < 0 {
+                end = first;
+            }
+        }
+        (x_min + first as i32, x_min + end.max(first) as i32)
+    }
+}
+
 impl std::fmt::Display for Triangle2D {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(f, "Triangle a:{}, b:{}, c:{}", self.a, self.b, self.c)
@@ -50,65 +292,19 @@ impl std::fmt::Display for Triangle2D {
 }
 
 impl Geometric2D for Triangle2D {
-    fn draw(&self, canvas: &mut raster::Image) {
-        let a = (self.c.x - self.a.x) * (self.b.y - self.a.y) - (self.b.x - self.a.x) * (self.c.y - self.a.y);
-        let x1 = self.a.x;
-        let y1 = self.a.y;
-        let mut x2 = self.b.x;
-        let mut y2 = self.b.y;
-        let mut x3 = self.c.x;
-        let mut y3 = self.c.y;
-        if a.abs() < 1e-10 {
-            return;
-        }
-        if a < 0.0 {
-            let mut t = x2;
-            x2 = x3;
-            x3 = t;
-            t = y2;
-            y2 = y3;
-            y3 = t;
-        }
-        let a_1 = y2 - y3;
-        let b_1 = x3 - x2;
-        let a_2 = y3 - y1;
-        let b_2 = x1 - x3;
-        let a_3 = y1 - y2;
-        let b_3 = x2 - x1;
-        let c_1 = -(a_1 * x2 + b_1 * y2);
-        let c_2 = -(a_2 * x3 + b_2 * y3);
-        let c_3 = -(a_3 * x1 + b_3 * y1);
-        let prp_alpha = 1.0f64 / (a_1 * x1 + b_1 * y1 + c_1);
-        let prp_beta = 1.0f64 / (a_2 * x2 + b_2 * y2 + c_2);
-        let prp_gamma = 1.0f64 / (a_3 * x3 + b_3 * y3 + c_3);
-        let x_min = x1.min(x2.min(x3)) as i32;
-        let x_max = x1.max(x2.max(x3)) as i32;
-        let y_min = y1.min(y2.min(y3)) as i32;
-        let y_max = y1.max(y2.max(y3)) as i32;
-        for xs in x_min..x_max {
-            for ys in y_min..y_max {
-                let x = xs as f64;
-                let y = ys as f64;
-                if (a_1 * x + b_1 * y + c_1) <= 0.0 && (a_2 * x + b_2 * y + c_2) <= 0.0 && (a_3 * x + b_3 * y + c_3) <= 0.0 {
-                    let alpha = (a_1 * x + b_1 * y + c_1) * prp_alpha;
-                    let beta = (a_2 * x + b_2 * y + c_2) * prp_beta;
-                    let gamma = (a_3 * x + b_3 * y + c_3) * prp_gamma;
-                    let r = interpolate_barycentric(self.a.get_color().r as f64, self.b.get_color().r as f64, self.c.get_color().r as f64, alpha, beta, gamma);
-                    let g = interpolate_barycentric(self.a.get_color().g as f64, self.b.get_color().g as f64, self.c.get_color().g as f64, alpha, beta, gamma);
-                    let b = interpolate_barycentric(self.a.get_color().b as f64, self.b.get_color().b as f64, self.c.get_color().b as f64, alpha, beta, gamma);
-                    canvas.set_pixel(xs, ys, Color::rgb(r as u8, g as u8, b as u8)).unwrap();
-                }
-            }
-        }
+    fn draw(&self, canvas: &mut dyn Canvas) {
+        self.draw_spans(canvas, false);
     }
 
-    fn draw_outline(&self, canvas: &mut raster::Image) {
-        let line1 = Line2D::new(self.a.clone(), self.b.clone());
-        line1.draw_outline(canvas);
-        let line2 = Line2D::new(self.b.clone(), self.c.clone());
-        line2.draw_outline(canvas);
-        let line3 = Line2D::new(self.c.clone(), self.a.clone());
-        line3.draw_outline(canvas);
+    fn draw_outline(&self, canvas: &mut dyn Canvas) {
+        //The border of the pixels `draw` paints, a Triangle without area has no pixels and shows its edges
+        if self.edge_functions().is_none() {
+            for edge in &self.edges() {
+                edge.draw_outline(canvas);
+            }
+            return;
+        }
+        self.draw_spans(canvas, true);
     }
 
     fn homogenize(&mut self) {
@@ -147,17 +343,150 @@ impl Geometric2D for Triangle2D {
         self.c.scale_from_point(sx, sy, p);
     }
 
-    fn draw_aa(&self, canvas: &mut raster::Image) {
-        self.draw_outline_aa(canvas);
-        self.draw(canvas);
+    fn apply_transform(&mut self, m: &Transform2D) {
+        self.a.apply_transform(m);
+        self.b.apply_transform(m);
+        self.c.apply_transform(m);
+    }
+
+    fn box_clone(&self) -> Box<dyn Geometric2D> {
+        Box::new(self.clone())
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        BoundingBox::from_points(vec![(self.a.x, self.a.y), (self.b.x, self.b.y), (self.c.x, self.c.y)])
+    }
+
+    fn contains(&self, p: &Point2D) -> bool {
+        match self.edge_functions() {
+            Some(edges) => edges.inside(FixedPoint::new(p.x, p.y)),
+            None => self.edges().iter().any(|edge| edge.contains(p))
+        }
+    }
+
+    fn distance(&self, p: &Point2D) -> f64 {
+        if self.contains(p) {
+            return 0.0;
+        }
+        self.edges().iter().map(|edge| edge.distance(p)).fold(f64::INFINITY, f64::min)
     }
 
-    fn draw_outline_aa(&self, canvas: &mut raster::Image) {
-        let line1 = Line2D::new(self.a.clone(), self.b.clone());
-        line1.draw_outline_aa(canvas);
-        let line2 = Line2D::new(self.b.clone(), self.c.clone());
-        line2.draw_outline_aa(canvas);
-        let line3 = Line2D::new(self.c.clone(), self.a.clone());
-        line3.draw_outline_aa(canvas);
+    fn draw_aa(&self, canvas: &mut dyn Canvas) {
+        let bounds = match self.bounding_box() {
+            Some(bounds) => bounds,
+            None => return
+        };
+        let mut rasterizer = CoverageRasterizer::for_canvas(canvas, &bounds);
+        rasterizer.polygon(&[(self.a.x, self.a.y), (self.b.x, self.b.y), (self.c.x, self.c.y)]);
+        let space = self.color_space.unwrap_or_else(|| ColorSpace::for_canvas(canvas));
+        let color_a = self.a.get_color();
+        let color_b = self.b.get_color();
+        let color_c = self.c.get_color();
+        rasterizer.for_each(|x, y, coverage| {
+            let (alpha, beta, gamma) = self.barycentric(x as f64 + 0.5, y as f64 + 0.5);
+            let depth = alpha * self.a.depth + beta * self.b.depth + gamma * self.c.depth;
+            if canvas.depth_test(x, y, depth) {
+                let color = interpolate_color_barycentric(&color_a, &color_b, &color_c, alpha, beta, gamma, space);
+                canvas.blend_pixel(x, y, &color, coverage);
+            }
+        });
     }
-}
\ No newline at end of file
+
+    fn draw_outline_aa(&self, canvas: &mut dyn Canvas) {
+        for edge in &self.edges() {
+            edge.draw_outline_aa(canvas);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use raster::Color;
+    use graphic::Framebuffer;
+
+    /// Counts how often each pixel is written
+    struct Counter {
+        width: i32,
+        height: i32,
+        counts: Vec<u32>
+    }
+
+    impl Canvas for Counter {
+        fn width(&self) -> i32 {
+            self.width
+        }
+
+        fn height(&self) -> i32 {
+            self.height
+        }
+
+        fn get_pixel(&self, x: i32, y: i32) -> Option<Color> {
+            if x < 0 || y < 0 || x >= self.width || y >= self.height { None } else { Some(Color::black()) }
+        }
+
+        fn set_pixel(&mut self, x: i32, y: i32, _color: &Color) {
+            if x >= 0 && y >= 0 && x < self.width && y < self.height {
+                self.counts[(y * self.width + x) as usize] += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn contains_matches_draw() {
+        let triangle = Triangle2D::new(Point2D::new_color(1.0, 1.0, Color::red()),
+                                       Point2D::new_color(2.0, 9.0, Color::red()),
+                                       Point2D::new_color(9.0, 4.0, Color::red()));
+        let mut canvas = Framebuffer::new(10, 10);
+        triangle.draw(&mut canvas);
+        for y in 0..10 {
+            for x in 0..10 {
+                let p = Point2D::new(x as f64 + 0.5, y as f64 + 0.5);
+                assert_eq!(triangle.contains(&p), canvas.get_pixel(x, y).unwrap().r == 255);
+            }
+        }
+        assert_eq!(0.0, triangle.distance(&Point2D::new(4.0, 4.0)));
+        assert_eq!(1.0, triangle.distance(&Point2D::new(0.0, 1.0)));
+    }
+
+    #[test]
+    fn shared_edges_are_painted_once() {
+        //A fan around a center off the pixel grid, covering the square from (2,2) to (30,30)
+        let center = Point2D::new(13.3, 17.7);
+        let corners = [(2.0, 2.0), (16.25, 2.0), (30.0, 2.0), (30.0, 19.5), (30.0, 30.0), (2.0, 30.0), (2.0, 11.1)];
+        let mut canvas = Counter { width: 32, height: 32, counts: vec![0; 32 * 32] };
+        for (i, &(x, y)) in corners.iter().enumerate() {
+            let (nx, ny) = corners[(i + 1) % corners.len()];
+            Triangle2D::new(center.clone(), Point2D::new(x, y), Point2D::new(nx, ny)).draw(&mut canvas);
+        }
+        for y in 0..32 {
+            for x in 0..32 {
+                let expected = if (2..30).contains(&x) && (2..30).contains(&y) { 1 } else { 0 };
+                assert_eq!(expected, canvas.counts[y * 32 + x], "pixel {}, {}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn anti_aliased_fill_covers_the_area() {
+        let triangle = Triangle2D::new(Point2D::new_color(1.2, 1.7, Color::red()),
+                                       Point2D::new_color(3.4, 18.1, Color::red()),
+                                       Point2D::new_color(17.9, 6.3, Color::red()));
+        let mut canvas = Framebuffer::new(20, 20);
+        canvas.clear(&Color::black());
+        triangle.draw_aa(&mut canvas);
+        let mut sum = 0.0;
+        for y in 0..20 {
+            for x in 0..20 {
+                sum += canvas.get_pixel(x, y).unwrap().r as f64 / 255.0;
+            }
+        }
+        let area = (0.5f64 * ((3.4 - 1.2) * (6.3 - 1.7) - (17.9 - 1.2) * (18.1 - 1.7))).abs();
+        assert!((sum - area).abs() < 0.5, "{} != {}", sum, area);
+        assert_eq!(255, canvas.get_pixel(6, 8).unwrap().r);
+        //Coordinates that are not numbers leave the Canvas untouched
+        canvas.clear(&Color::black());
+        Triangle2D::new(Point2D::new(f64::NAN, 1.0), Point2D::new(3.0, 18.0), Point2D::new(17.0, 6.0)).draw_aa(&mut canvas);
+        assert!(canvas.as_bytes().chunks(4).all(|pixel| pixel[0] == 0));
+    }
+}
@@ -0,0 +1,601 @@
+extern crate raster;
+
+use std::f64::consts::PI;
+use raster::Color;
+use geometric::{Geometric2D, Transform2D};
+use graphic::{Canvas, BlendMode, Framebuffer, HdrBuffer, srgb_to_linear, linear_to_srgb};
+use graphic::layer::{self, LayerBuffer};
+
+/// Anti-aliasing a Renderer applies on top of what each Geometric-Object draws
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Antialiasing {
+    /// Draws every Geometric-Object as it is
+    #[default]
+    Off,
+    /// Draws the scene at `factor` times the resolution on both axes and filters it down
+    Supersampling {
+        /// Samples per pixel along each axis, 1 draws as it is
+        factor: u32,
+        /// Filter combining the samples into pixels
+        filter: DownsampleFilter
+    },
+    /// Draws every Geometric-Object once per sample position and keeps a color per sample
+    ///
+    /// The samples a Geometric-Object covers are recorded per pixel in a bit mask, so edges are smoothed even for
+    /// fills whose own coverage is unknown, and edges shared by two shapes leave no seam.
+    /// Samples are blended with the blend mode of an HdrBuffer, sample by sample.
+    Multisampling(SamplePattern)
+}
+
+/// Filter combining the samples of a supersampled image into pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DownsampleFilter {
+    /// Averages the samples inside a pixel
+    #[default]
+    Box,
+    /// Weights the samples linearly by their distance to the pixel center, reaching into the neighbours
+    Tent,
+    /// Windowed sinc over two pixels in each direction, the sharpest but may ring at hard edges
+    Lanczos
+}
+
+impl DownsampleFilter {
+    /// Returns the distance in pixels beyond which samples have no weight
+    fn radius(self) -> f64 {
+        match self {
+            DownsampleFilter::Box => 0.5,
+            DownsampleFilter::Tent => 1.0,
+            DownsampleFilter::Lanczos => 2.0
+        }
+    }
+
+    /// Returns the weight of a sample `t` pixels away from the pixel center
+    fn weight(self, t: f64) -> f64 {
+        let t = t.abs();
+        match self {
+            DownsampleFilter::Box => if t <= 0.5 { 1.0 } else { 0.0 },
+            DownsampleFilter::Tent => (1.0 - t).max(0.0),
+            DownsampleFilter::Lanczos => if t < 1e-9 {
+                1.0
+            } else if t < 2.0 {
+                2.0 * (PI * t).sin() * (PI * t / 2.0).sin() / (PI * PI * t * t)
+            } else {
+                0.0
+            }
+        }
+    }
+
+    /// Returns the weights of the samples around a pixel, relative to its first sample
+    fn weights(self, factor: i32) -> Vec<(i32, f64)> {
+        let reach = (self.radius() * factor as f64).ceil() as i32;
+        let center = factor as f64 / 2.0;
+        let weights: Vec<(i32, f64)> = (-reach..factor + reach)
+            .map(|k| (k, self.weight((k as f64 + 0.5 - center) / factor as f64)))
+            .filter(|&(_, w)| w != 0.0)
+            .collect();
+        let sum: f64 = weights.iter().map(|&(_, w)| w).sum();
+        weights.into_iter().map(|(k, w)| (k, w / sum)).collect()
+    }
+}
+
+/// Positions within a pixel that Multisampling tests coverage at
+#[derive(Debug, Clone, PartialEq)]
+pub struct SamplePattern {
+    offsets: Vec<(f64, f64)>
+}
+
+impl SamplePattern {
+    /// Most samples a pattern can have, one bit of the coverage mask each
+    pub const MAX_SAMPLES: usize = 32;
+
+    /// Returns a pattern sampling the given positions
+    ///
+    /// # Arguments
+    ///
+    /// * `offsets` - Positions between 0..1 from the top left corner of a pixel, only the first 32 are used,
+    ///   without any a single sample at the pixel center
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// use graphic_library::graphic::SamplePattern;
+    /// fn main(){
+    /// //Two samples on the diagonal
+    /// let pattern = SamplePattern::new(vec![(0.25, 0.25), (0.75, 0.75)]);
+    /// assert_eq!(2, pattern.len());
+    /// }
+    /// ```
+    pub fn new(offsets: Vec<(f64, f64)>) -> SamplePattern {
+        if offsets.is_empty() {
+            return SamplePattern { offsets: vec![(0.5, 0.5)] };
+        }
+        SamplePattern {
+            offsets: offsets.into_iter()
+                .take(SamplePattern::MAX_SAMPLES)
+                .map(|(x, y)| (x.clamp(0.0, 1.0), y.clamp(0.0, 1.0)))
+                .collect()
+        }
+    }
+
+    /// Returns `n` by `n` samples on a regular grid, at most 5 by 5
+    pub fn grid(n: u32) -> SamplePattern {
+        let n = n.clamp(1, 5);
+        let step = 1.0 / n as f64;
+        SamplePattern::new((0..n * n).map(|i| (((i % n) as f64 + 0.5) * step, ((i / n) as f64 + 0.5) * step)).collect())
+    }
+
+    /// Returns the four samples of a rotated grid, which resolve near horizontal and vertical edges better than a 2 by 2 grid
+    pub fn rotated_grid() -> SamplePattern {
+        SamplePattern::new(vec![(0.375, 0.125), (0.875, 0.375), (0.125, 0.625), (0.625, 0.875)])
+    }
+
+    /// Returns the sample positions
+    pub fn offsets(&self) -> &[(f64, f64)] {
+        &self.offsets
+    }
+
+    /// Returns the number of samples per pixel
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Returns whether the pattern has no samples, which never happens since an empty pattern samples the pixel center
+    pub fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+}
+
+impl Default for SamplePattern {
+    fn default() -> SamplePattern {
+        SamplePattern::rotated_grid()
+    }
+}
+
+/// Pixel buffer a supersampled image can be drawn into and filtered from
+pub(crate) trait SampleBuffer: LayerBuffer + Clone {
+    /// Returns a pixel as RGBA in the space samples are filtered in
+    fn sample(&self, x: i32, y: i32) -> [f32; 4];
+
+    /// Overwrites a pixel with RGBA in the space samples are filtered in
+    fn store(&mut self, x: i32, y: i32, value: [f32; 4]);
+
+    /// Returns how samples drawn into the buffer are combined with the samples underneath
+    fn sample_blend_mode(&self) -> BlendMode {
+        BlendMode::Normal
+    }
+}
+
+impl SampleBuffer for Framebuffer {
+    fn sample(&self, x: i32, y: i32) -> [f32; 4] {
+        let color = self.get_pixel(x, y).unwrap();
+        let channel = |v: u8| if self.linear_blending() { srgb_to_linear(v) } else { v as f32 / 255.0 };
+        [channel(color.r), channel(color.g), channel(color.b), color.a as f32 / 255.0]
+    }
+
+    fn store(&mut self, x: i32, y: i32, value: [f32; 4]) {
+        let linear = self.linear_blending();
+        let channel = |v: f32| if linear { linear_to_srgb(v) } else { (v.clamp(0.0, 1.0) * 255.0).round() as u8 };
+        let alpha = (value[3].clamp(0.0, 1.0) * 255.0).round() as u8;
+        self.set_pixel(x, y, &Color::rgba(channel(value[0]), channel(value[1]), channel(value[2]), alpha));
+    }
+}
+
+impl SampleBuffer for HdrBuffer {
+    fn sample(&self, x: i32, y: i32) -> [f32; 4] {
+        self.get(x, y).unwrap()
+    }
+
+    fn store(&mut self, x: i32, y: i32, value: [f32; 4]) {
+        self.set(x, y, value);
+    }
+
+    fn sample_blend_mode(&self) -> BlendMode {
+        self.blend_mode()
+    }
+}
+
+/// Draws into a copy of `buffer` enlarged `factor` times and filters the changes back into `buffer`
+///
+/// Only what `render` changes is filtered, pixels it leaves alone keep their values exactly.
+pub(crate) fn supersample<B: SampleBuffer, F: FnOnce(&mut B)>(buffer: &mut B, factor: u32, filter: DownsampleFilter, render: F) {
+    let factor = factor.max(1) as i32;
+    let (width, height) = (buffer.width(), buffer.height());
+    let mut before = buffer.blank(width * factor, height * factor);
+    for y in 0..height * factor {
+        for x in 0..width * factor {
+            before.store(x, y, buffer.sample(x / factor, y / factor));
+        }
+    }
+    let mut after = before.clone();
+    render(&mut after);
+    //The filter is separable, rows are filtered first and the result column by column
+    let weights = filter.weights(factor);
+    let mut rows = vec![[0.0f32; 4]; (width * height * factor) as usize];
+    for y in 0..height * factor {
+        for x in 0..width {
+            let sum = &mut rows[(y * width + x) as usize];
+            for &(k, w) in &weights {
+                let sx = x * factor + k;
+                if sx >= 0 && sx < width * factor {
+                    let (a, b) = (after.sample(sx, y), before.sample(sx, y));
+                    for c in 0..4 {
+                        sum[c] += w as f32 * (a[c] - b[c]);
+                    }
+                }
+            }
+        }
+    }
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = [0.0f32; 4];
+            for &(k, w) in &weights {
+                let sy = y * factor + k;
+                if sy >= 0 && sy < height * factor {
+                    for (sum, row) in sum.iter_mut().zip(&rows[(sy * width + x) as usize]) {
+                        *sum += w as f32 * row;
+                    }
+                }
+            }
+            if sum.iter().any(|c| c.abs() > 1e-6) {
+                let mut value = buffer.sample(x, y);
+                for (value, sum) in value.iter_mut().zip(&sum) {
+                    *value += sum;
+                }
+                buffer.store(x, y, value);
+            }
+        }
+    }
+}
+
+/// Draws a Geometric-Object once per sample of a pattern and blends it by the share of covered samples
+pub(crate) fn multisample(geo: &dyn Geometric2D, canvas: &mut dyn Canvas, pattern: &SamplePattern,
+                          draw: &dyn Fn(&dyn Geometric2D, &mut dyn Canvas)) {
+    let (clip_x0, clip_y0, clip_x1, clip_y1) = canvas.clip_rect();
+    let (mut x0, mut y0, mut x1, mut y1) = (clip_x0.max(0), clip_y0.max(0), clip_x1.min(canvas.width()), clip_y1.min(canvas.height()));
+    //Shifting by up to a pixel and anti-aliasing may reach beyond the bounds
+    if let Some(bounds) = geo.bounding_box().map(|bounds| bounds.expand(3.0)) {
+        x0 = x0.max(bounds.min_x.floor() as i32);
+        y0 = y0.max(bounds.min_y.floor() as i32);
+        x1 = x1.min(bounds.max_x.ceil() as i32);
+        y1 = y1.min(bounds.max_y.ceil() as i32);
+    }
+    if x0 >= x1 || y0 >= y1 {
+        return;
+    }
+    let mut samples = SampleMasks::new(canvas.width(), canvas.height(), (x0, y0, x1, y1));
+    for (i, &(sx, sy)) in pattern.offsets().iter().enumerate() {
+        //Moves the sample position onto the pixel center every Geometric-Object samples at
+        let mut shifted = geo.box_clone();
+        shifted.apply_transform(&Transform2D::translation(0.5 - sx, 0.5 - sy));
+        samples.bit = 1 << i;
+        draw(&*shifted, &mut samples);
+    }
+    samples.resolve(canvas, pattern.len());
+}
+
+/// Draws into a Canvas storing a color per sample of each pixel and writes the average of the samples back into `buffer`
+///
+/// Only pixels `render` touches are written back, the others keep their values exactly.
+pub(crate) fn resolve_samples<B: SampleBuffer, F: FnOnce(&mut MultisampleBuffer)>(buffer: &mut B, samples: usize, render: F) {
+    let mut multisampled = MultisampleBuffer::new(buffer, samples);
+    render(&mut multisampled);
+    for (i, &touched) in multisampled.touched.iter().enumerate() {
+        if touched {
+            let (x, y) = (i as i32 % multisampled.width, i as i32 / multisampled.width);
+            buffer.store(x, y, multisampled.average(i));
+        }
+    }
+}
+
+/// Canvas storing a color per sample of each pixel, blending by coverage mask
+pub(crate) struct MultisampleBuffer {
+    width: i32,
+    height: i32,
+    samples: usize,
+    linear_blending: bool,
+    blend_mode: BlendMode,
+    /// RGBA of every sample in the space of the resolved buffer, pixel by pixel
+    colors: Vec<[f32; 4]>,
+    /// Whether any sample of a pixel was written
+    touched: Vec<bool>
+}
+
+impl MultisampleBuffer {
+    fn new<B: SampleBuffer>(buffer: &B, samples: usize) -> MultisampleBuffer {
+        let (width, height) = (buffer.width(), buffer.height());
+        let samples = samples.clamp(1, SamplePattern::MAX_SAMPLES);
+        let mut colors = Vec::with_capacity((width * height) as usize * samples);
+        for y in 0..height {
+            for x in 0..width {
+                let value = buffer.sample(x, y);
+                colors.extend((0..samples).map(|_| value));
+            }
+        }
+        MultisampleBuffer {
+            width,
+            height,
+            samples,
+            linear_blending: buffer.linear_blending(),
+            blend_mode: buffer.sample_blend_mode(),
+            colors,
+            touched: vec![false; (width * height) as usize]
+        }
+    }
+
+    fn offset(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            None
+        } else {
+            Some((y * self.width + x) as usize)
+        }
+    }
+
+    /// Returns the average of the samples of a pixel
+    fn average(&self, pixel: usize) -> [f32; 4] {
+        let mut sum = [0.0f32; 4];
+        for sample in &self.colors[pixel * self.samples..(pixel + 1) * self.samples] {
+            for (sum, c) in sum.iter_mut().zip(sample) {
+                *sum += c;
+            }
+        }
+        let n = self.samples as f32;
+        [sum[0] / n, sum[1] / n, sum[2] / n, sum[3] / n]
+    }
+
+    /// Converts a color into the space samples are stored in
+    fn decode(&self, color: &Color) -> [f32; 4] {
+        let channel = |v: u8| if self.linear_blending { srgb_to_linear(v) } else { v as f32 / 255.0 };
+        [channel(color.r), channel(color.g), channel(color.b), color.a as f32 / 255.0]
+    }
+
+    /// Blends a color over the selected samples of a pixel with the blend mode of the buffer
+    fn blend_masked(&mut self, x: i32, y: i32, color: &Color, coverage: f64, mask: u32) {
+        let pixel = match self.offset(x, y) {
+            Some(pixel) => pixel,
+            None => return
+        };
+        let src = self.decode(color);
+        let coverage = coverage.clamp(0.0, 1.0) as f32;
+        let mode = self.blend_mode;
+        for (i, dst) in self.colors[pixel * self.samples..(pixel + 1) * self.samples].iter_mut().enumerate() {
+            if mask & (1 << i) != 0 {
+                *dst = mode.blend(*dst, src, coverage);
+            }
+        }
+        self.touched[pixel] = true;
+    }
+}
+
+impl Canvas for MultisampleBuffer {
+    fn width(&self) -> i32 {
+        self.width
+    }
+
+    fn height(&self) -> i32 {
+        self.height
+    }
+
+    fn get_pixel(&self, x: i32, y: i32) -> Option<Color> {
+        self.offset(x, y).map(|pixel| {
+            let value = self.average(pixel);
+            let channel = |v: f32| if self.linear_blending { linear_to_srgb(v) } else { (v.clamp(0.0, 1.0) * 255.0).round() as u8 };
+            Color::rgba(channel(value[0]), channel(value[1]), channel(value[2]), (value[3].clamp(0.0, 1.0) * 255.0).round() as u8)
+        })
+    }
+
+    fn set_pixel(&mut self, x: i32, y: i32, color: &Color) {
+        if self.blend_mode != BlendMode::Normal {
+            return self.blend_pixel(x, y, color, 1.0);
+        }
+        if let Some(pixel) = self.offset(x, y) {
+            let value = self.decode(color);
+            for sample in &mut self.colors[pixel * self.samples..(pixel + 1) * self.samples] {
+                *sample = value;
+            }
+            self.touched[pixel] = true;
+        }
+    }
+
+    fn blend_pixel(&mut self, x: i32, y: i32, color: &Color, coverage: f64) {
+        self.blend_masked(x, y, color, coverage, u32::MAX);
+    }
+
+    fn linear_blending(&self) -> bool {
+        self.linear_blending
+    }
+
+    fn blend_samples(&mut self, x: i32, y: i32, color: &Color, mask: u32, _samples: usize) {
+        self.blend_masked(x, y, color, 1.0, mask);
+    }
+
+    fn draw_layer(&mut self, opacity: f64, draw: &mut dyn FnMut(&mut dyn Canvas)) {
+        layer::draw_layer(self, opacity, draw);
+    }
+}
+
+impl LayerBuffer for MultisampleBuffer {
+    fn blank(&self, width: i32, height: i32) -> MultisampleBuffer {
+        let (width, height) = (width.max(0), height.max(0));
+        MultisampleBuffer {
+            width,
+            height,
+            samples: self.samples,
+            linear_blending: self.linear_blending,
+            blend_mode: self.blend_mode,
+            colors: vec![[0.0; 4]; (width * height) as usize * self.samples],
+            touched: vec![false; (width * height) as usize]
+        }
+    }
+
+    /// Blends every sample of the layer over the same sample underneath, layers with `BlendMode::Normal` use the blend mode of the buffer
+    fn composite_layer(&mut self, layer: &MultisampleBuffer, x: i32, y: i32, opacity: f64, mode: BlendMode) {
+        let mode = if mode == BlendMode::Normal { self.blend_mode } else { mode };
+        let opacity = opacity.clamp(0.0, 1.0) as f32;
+        let n = self.samples;
+        for ly in (-y).max(0)..layer.height.min(self.height - y) {
+            for lx in (-x).max(0)..layer.width.min(self.width - x) {
+                let src = layer.offset(lx, ly).unwrap();
+                if !layer.touched[src] {
+                    continue;
+                }
+                let dst = self.offset(x + lx, y + ly).unwrap();
+                for (dst, src) in self.colors[dst * n..(dst + 1) * n].iter_mut().zip(&layer.colors[src * n..(src + 1) * n]) {
+                    if src[3] > 0.0 {
+                        *dst = mode.blend(*dst, *src, opacity);
+                    }
+                }
+                self.touched[dst] = true;
+            }
+        }
+    }
+}
+
+/// Canvas recording which samples of each pixel a Geometric-Object covers
+struct SampleMasks {
+    width: i32,
+    height: i32,
+    /// Pixels recorded, `x0, y0, x1, y1`
+    rect: (i32, i32, i32, i32),
+    /// Bit of the sample currently drawn
+    bit: u32,
+    masks: Vec<u32>,
+    /// Sum of the premultiplied colors of the covered samples
+    colors: Vec<[f64; 4]>,
+    /// Closest depth drawn into each pixel
+    depths: Vec<f64>
+}
+
+impl SampleMasks {
+    fn new(width: i32, height: i32, rect: (i32, i32, i32, i32)) -> SampleMasks {
+        let size = ((rect.2 - rect.0) * (rect.3 - rect.1)) as usize;
+        SampleMasks {
+            width,
+            height,
+            rect,
+            bit: 1,
+            masks: vec![0; size],
+            colors: vec![[0.0; 4]; size],
+            depths: vec![f64::INFINITY; size]
+        }
+    }
+
+    fn offset(&self, x: i32, y: i32) -> Option<usize> {
+        let (x0, y0, x1, y1) = self.rect;
+        if x < x0 || y < y0 || x >= x1 || y >= y1 {
+            None
+        } else {
+            Some(((y - y0) * (x1 - x0) + x - x0) as usize)
+        }
+    }
+
+    /// Blends the average color of the covered samples over them
+    fn resolve(&self, canvas: &mut dyn Canvas, samples: usize) {
+        let (x0, y0, x1, _) = self.rect;
+        for (i, &mask) in self.masks.iter().enumerate() {
+            let [r, g, b, a] = self.colors[i];
+            if mask == 0 || a <= 0.0 {
+                continue;
+            }
+            let channel = |c: f64| (c / a).round().clamp(0.0, 255.0) as u8;
+            let alpha = (a / mask.count_ones() as f64 * 255.0).round().clamp(0.0, 255.0) as u8;
+            //Writes without depth count as closest
+            let depth = if self.depths[i].is_finite() { self.depths[i] } else { 0.0 };
+            let x = x0 + i as i32 % (x1 - x0);
+            let y = y0 + i as i32 / (x1 - x0);
+            if canvas.depth_test(x, y, depth) {
+                canvas.blend_samples(x, y, &Color::rgba(channel(r), channel(g), channel(b), alpha), mask, samples);
+            }
+        }
+    }
+}
+
+impl Canvas for SampleMasks {
+    fn width(&self) -> i32 {
+        self.width
+    }
+
+    fn height(&self) -> i32 {
+        self.height
+    }
+
+    fn get_pixel(&self, x: i32, y: i32) -> Option<Color> {
+        self.offset(x, y).map(|_| Color::rgba(0, 0, 0, 0))
+    }
+
+    fn set_pixel(&mut self, x: i32, y: i32, color: &Color) {
+        self.blend_pixel(x, y, color, 1.0);
+    }
+
+    fn blend_pixel(&mut self, x: i32, y: i32, color: &Color, coverage: f64) {
+        if let Some(i) = self.offset(x, y) {
+            //Each sample counts once, even if a Geometric-Object paints a pixel twice
+            if coverage <= 0.0 || self.masks[i] & self.bit != 0 {
+                return;
+            }
+            self.masks[i] |= self.bit;
+            let a = color.a as f64 / 255.0 * coverage.min(1.0);
+            let sum = &mut self.colors[i];
+            sum[0] += color.r as f64 * a;
+            sum[1] += color.g as f64 * a;
+            sum[2] += color.b as f64 * a;
+            sum[3] += a;
+        }
+    }
+
+    fn clip_rect(&self) -> (i32, i32, i32, i32) {
+        self.rect
+    }
+
+    fn depth_test(&mut self, x: i32, y: i32, depth: f64) -> bool {
+        if let Some(i) = self.offset(x, y) {
+            self.depths[i] = self.depths[i].min(depth);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_weights_sum_to_one() {
+        for &filter in &[DownsampleFilter::Box, DownsampleFilter::Tent, DownsampleFilter::Lanczos] {
+            for factor in 1..5 {
+                let sum: f64 = filter.weights(factor).iter().map(|&(_, w)| w).sum();
+                assert!((sum - 1.0).abs() < 1e-9);
+            }
+        }
+        assert_eq!(vec![(0, 0.5), (1, 0.5)], DownsampleFilter::Box.weights(2));
+    }
+
+    #[test]
+    fn supersampling_keeps_untouched_pixels() {
+        let mut framebuffer = Framebuffer::new(4, 4);
+        framebuffer.clear(&Color::rgb(10, 20, 30));
+        supersample(&mut framebuffer, 2, DownsampleFilter::Lanczos, |buffer| buffer.set_pixel(0, 0, &Color::white()));
+        assert!(framebuffer.get_pixel(0, 0).unwrap().r > 10);
+        let far = framebuffer.get_pixel(3, 3).unwrap();
+        assert_eq!((10, 20, 30), (far.r, far.g, far.b));
+    }
+
+    #[test]
+    fn empty_pattern_samples_the_pixel_center() {
+        assert_eq!(&[(0.5, 0.5)], SamplePattern::new(vec![]).offsets());
+        assert!(!SamplePattern::new(vec![]).is_empty());
+    }
+
+    #[test]
+    fn multisampling_honors_the_blend_mode_of_hdr_buffers() {
+        let mut hdr = HdrBuffer::new(2, 1);
+        hdr.set_blend_mode(BlendMode::Additive);
+        hdr.clear([1.0, 0.0, 0.0, 1.0]);
+        resolve_samples(&mut hdr, 4, |buffer| {
+            buffer.set_pixel(0, 0, &Color::red());
+            buffer.blend_samples(1, 0, &Color::red(), 0b0011, 4);
+        });
+        assert_eq!([2.0, 0.0, 0.0, 1.0], hdr.get(0, 0).unwrap());
+        assert_eq!([1.5, 0.0, 0.0, 1.0], hdr.get(1, 0).unwrap());
+    }
+}
@@ -0,0 +1,144 @@
+extern crate raster;
+
+use raster::Color;
+use geometric::Point2D;
+use graphic::{Canvas, ColorSpace, interpolate_color};
+
+/// What a Renderer paints before drawing a frame
+#[derive(Debug)]
+pub enum Background {
+    /// Paints every pixel with one color
+    Color(Color),
+    /// Interpolates between the colors of two Points along the line connecting them
+    ///
+    /// Pixels before `start` take its color, pixels after `end` take the color of `end`.
+    Gradient {
+        /// Point with the color of the start of the gradient
+        start: Point2D,
+        /// Point with the color of the end of the gradient
+        end: Point2D,
+        /// Color space the colors are interpolated in
+        color_space: ColorSpace
+    },
+    /// Stretches an image over the whole Canvas
+    Image(raster::Image)
+}
+
+impl Background {
+    /// Overwrites every pixel of a Canvas with the Background
+    ///
+    /// # Arguments
+    ///
+    /// * `canvas` - Canvas to paint
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// extern crate raster;
+    /// use raster::Color;
+    /// use graphic_library::geometric::Point2D;
+    /// use graphic_library::graphic::{Background, Canvas, ColorSpace, Framebuffer};
+    /// fn main(){
+    /// let mut framebuffer = Framebuffer::new(100, 100);
+    /// //Fades from blue at the top to white at the bottom
+    /// let background = Background::Gradient {
+    ///     start: Point2D::new_color(0.0, 0.0, Color::blue()),
+    ///     end: Point2D::new_color(0.0, 99.0, Color::white()),
+    ///     color_space: ColorSpace::Oklab
+    /// };
+    /// background.paint(&mut framebuffer);
+    /// assert_eq!(255, framebuffer.get_pixel(50, 99).unwrap().r);
+    /// }
+    /// ```
+    pub fn paint(&self, canvas: &mut dyn Canvas) {
+        match *self {
+            Background::Color(ref color) => {
+                for y in 0..canvas.height() {
+                    canvas.fill_span(0, canvas.width(), y, color);
+                }
+            }
+            Background::Gradient { ref start, ref end, color_space } => {
+                let dx = end.x - start.x;
+                let dy = end.y - start.y;
+                let length = dx * dx + dy * dy;
+                let (from, to) = (start.get_color(), end.get_color());
+                if color_space == ColorSpace::Srgb {
+                    //Along a row the interpolation value changes by the same amount from pixel to pixel
+                    for y in 0..canvas.height() {
+                        let t = if length == 0.0 {
+                            (0.0, 0.0)
+                        } else {
+                            ((-start.x * dx + (y as f64 - start.y) * dy) / length, dx / length)
+                        };
+                        canvas.fill_gradient_span(0, canvas.width(), y, &from, &to, t);
+                    }
+                    return;
+                }
+                for y in 0..canvas.height() {
+                    for x in 0..canvas.width() {
+                        let t = if length == 0.0 {
+                            0.0
+                        } else {
+                            ((x as f64 - start.x) * dx + (y as f64 - start.y) * dy) / length
+                        };
+                        canvas.set_pixel(x, y, &interpolate_color(&from, &to, t.clamp(0.0, 1.0), color_space));
+                    }
+                }
+            }
+            Background::Image(ref image) => {
+                let (width, height) = (canvas.width(), canvas.height());
+                if image.width <= 0 || image.height <= 0 {
+                    return;
+                }
+                for y in 0..height {
+                    for x in 0..width {
+                        let sx = (x as i64 * image.width as i64 / width as i64) as i32;
+                        let sy = (y as i64 * image.height as i64 / height as i64) as i32;
+                        if let Some(color) = Canvas::get_pixel(image, sx, sy) {
+                            canvas.set_pixel(x, y, &color);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Default for Background {
+    fn default() -> Background {
+        Background::Color(Color::black())
+    }
+}
+
+impl Clone for Background {
+    fn clone(&self) -> Background {
+        match *self {
+            Background::Color(ref color) => Background::Color(color.clone()),
+            Background::Gradient { ref start, ref end, color_space } => Background::Gradient {
+                start: start.clone(),
+                end: end.clone(),
+                color_space
+            },
+            Background::Image(ref image) => Background::Image(image.clone())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphic::Framebuffer;
+
+    #[test]
+    fn paints_every_pixel() {
+        let mut framebuffer = Framebuffer::new(3, 2);
+        Background::Color(Color::red()).paint(&mut framebuffer);
+        assert!(framebuffer.as_bytes().chunks(4).all(|p| p == [255, 0, 0, 255]));
+        let mut image = raster::Image::blank(1, 2);
+        Canvas::set_pixel(&mut image, 0, 1, &Color::blue());
+        Background::Image(image).paint(&mut framebuffer);
+        assert_eq!(0, framebuffer.get_pixel(2, 0).unwrap().b);
+        assert_eq!(255, framebuffer.get_pixel(2, 1).unwrap().b);
+    }
+}
@@ -0,0 +1,116 @@
+use std;
+
+use geometric::{BoundingBox, Transform2D};
+
+/// Maps world coordinates of the shapes onto the pixels of an image
+///
+/// The center of the Camera ends up in the middle of the image.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    /// X-Coordinate of the world shown in the middle of the image
+    pub center_x: f64,
+    /// Y-Coordinate of the world shown in the middle of the image
+    pub center_y: f64,
+    /// Pixels per world unit
+    pub zoom: f64,
+    /// Angle in degrees the Camera is turned by, positive values turn the world counterclockwise on screen
+    pub rotation: f64
+}
+
+impl Camera {
+    /// Returns a Camera
+    ///
+    /// # Arguments
+    ///
+    /// * `center_x` - X-Coordinate of the world shown in the middle of the image
+    /// * `center_y` - Y-Coordinate of the world shown in the middle of the image
+    /// * `zoom` - Pixels per world unit
+    /// * `rotation` - Angle in degrees the Camera is turned by
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// use graphic_library::graphic::Camera;
+    /// fn main(){
+    /// //Shows the world around (0,0) four times enlarged
+    /// let camera = Camera::new(0.0, 0.0, 4.0, 0.0);
+    /// assert_eq!((58.0, 50.0), camera.to_screen(100, 100).apply(2.0, 0.0));
+    /// }
+    /// ```
+    pub fn new(center_x: f64, center_y: f64, zoom: f64, rotation: f64) -> Camera {
+        Camera { center_x, center_y, zoom, rotation }
+    }
+
+    /// Returns an unrotated Camera showing a region of the world as large as possible
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - Region of the world to show
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    ///
+    pub fn fit(region: &BoundingBox, width: i32, height: i32) -> Camera {
+        let (center_x, center_y) = region.center();
+        let zoom = (width as f64 / region.width()).min(height as f64 / region.height());
+        Camera::new(center_x, center_y, if zoom.is_finite() && zoom > 0.0 { zoom } else { 1.0 }, 0.0)
+    }
+
+    /// Returns the Transformation from world coordinates to the pixels of an image
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    ///
+    pub fn to_screen(&self, width: i32, height: i32) -> Transform2D {
+        Transform2D::translation(-self.center_x, -self.center_y)
+            .then(&Transform2D::rotation(-self.rotation))
+            .then(&Transform2D::scaling(self.zoom, self.zoom))
+            .then(&Transform2D::translation(width as f64 / 2.0, height as f64 / 2.0))
+    }
+
+    /// Returns the Transformation from the pixels of an image to world coordinates or `None` for a zoom of 0
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    ///
+    pub fn to_world(&self, width: i32, height: i32) -> Option<Transform2D> {
+        self.to_screen(width, height).inverse()
+    }
+
+    /// Returns the region of the world visible in an image
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - Width of the image in pixels
+    /// * `height` - Height of the image in pixels
+    ///
+    pub fn visible_area(&self, width: i32, height: i32) -> Option<BoundingBox> {
+        self.to_world(width, height).map(|m| BoundingBox::new(0.0, 0.0, width as f64, height as f64).transform(&m))
+    }
+}
+
+impl std::fmt::Display for Camera {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Camera at ({}, {}), zoom:{}, rotation:{}", self.center_x, self.center_y, self.zoom, self.rotation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_visible_area_onto_image() {
+        let camera = Camera::new(100.0, 50.0, 2.0, 90.0);
+        let (x, y) = camera.to_screen(40, 20).apply(100.0, 55.0);
+        assert!((x - 30.0).abs() < 1e-9 && (y - 10.0).abs() < 1e-9);
+        let area = camera.visible_area(40, 20).unwrap();
+        assert!((area.width() - 10.0).abs() < 1e-9 && (area.height() - 20.0).abs() < 1e-9);
+        let fitted = Camera::fit(&BoundingBox::new(0.0, 0.0, 10.0, 40.0), 100, 100);
+        assert_eq!((5.0, 20.0, 2.5), (fitted.center_x, fitted.center_y, fitted.zoom));
+    }
+}
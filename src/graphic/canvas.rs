@@ -0,0 +1,445 @@
+extern crate raster;
+
+use raster::Color;
+use graphic::{ColorSpace, Framebuffer, srgb_to_linear, linear_to_srgb, interpolate_color};
+use graphic::layer;
+
+/// Operation combining a new color with the pixel underneath
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlendMode {
+    /// Paints the new color over the pixel underneath
+    Normal,
+    /// Adds the new color onto the pixel underneath
+    Additive,
+    /// Multiplies the new color with the pixel underneath, darkening it
+    Multiply,
+    /// Inverts, multiplies and inverts again, lightening the pixel underneath
+    Screen
+}
+
+impl BlendMode {
+    /// Combines a channel of the new color with the channel underneath, both between 0..1
+    ///
+    /// # Arguments
+    ///
+    /// * `dst` - Channel underneath
+    /// * `src` - Channel of the new color
+    ///
+    pub fn mix(self, dst: f32, src: f32) -> f32 {
+        match self {
+            BlendMode::Normal => src,
+            BlendMode::Additive => dst + src,
+            BlendMode::Multiply => dst * src,
+            BlendMode::Screen => dst + src - dst * src
+        }
+    }
+
+    /// Blends straight alpha RGBA over a pixel, channels may exceed 1.0 in linear light
+    ///
+    /// # Arguments
+    ///
+    /// * `dst` - Pixel underneath
+    /// * `src` - Color on top
+    /// * `coverage` - Covered area between 0..1, multiplies the alpha of `src`
+    ///
+    pub(crate) fn blend(self, dst: [f32; 4], src: [f32; 4], coverage: f32) -> [f32; 4] {
+        let sa = src[3] * coverage;
+        if self == BlendMode::Additive {
+            return [dst[0] + src[0] * sa, dst[1] + src[1] * sa, dst[2] + src[2] * sa, (dst[3] + sa).min(1.0)];
+        }
+        let oa = sa + dst[3] * (1.0 - sa);
+        if oa <= 0.0 {
+            return [0.0; 4];
+        }
+        let channel = |i: usize| (self.mix(dst[i], src[i]) * sa + dst[i] * dst[3] * (1.0 - sa)) / oa;
+        [channel(0), channel(1), channel(2), oa]
+    }
+}
+
+/// Drawing Surface every Geometric-Object renders into
+///
+/// Implement this Trait for your own pixel storage to draw into it without copying.
+pub trait Canvas {
+    /// Returns the width of the Canvas in pixels
+    fn width(&self) -> i32;
+
+    /// Returns the height of the Canvas in pixels
+    fn height(&self) -> i32;
+
+    /// Returns the color of a pixel or `None` if it lies outside of the Canvas
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - X-Coordinate of the pixel
+    /// * `y` - Y-Coordinate of the pixel
+    ///
+    fn get_pixel(&self, x: i32, y: i32) -> Option<Color>;
+
+    /// Overwrites a pixel, pixels outside of the Canvas are ignored
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - X-Coordinate of the pixel
+    /// * `y` - Y-Coordinate of the pixel
+    /// * `color` - New color of the pixel
+    ///
+    fn set_pixel(&mut self, x: i32, y: i32, color: &Color);
+
+    /// Blends a color over a pixel, pixels outside of the Canvas are ignored
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - X-Coordinate of the pixel
+    /// * `y` - Y-Coordinate of the pixel
+    /// * `color` - Color to blend over the pixel
+    /// * `coverage` - Covered area of the pixel between 0..1, multiplies the alpha of `color`
+    ///
+    fn blend_pixel(&mut self, x: i32, y: i32, color: &Color, coverage: f64) {
+        if let Some(dst) = self.get_pixel(x, y) {
+            let blended = if self.linear_blending() {
+                blend_linear(&dst, color, coverage)
+            } else {
+                blend(&dst, color, coverage)
+            };
+            self.set_pixel(x, y, &blended);
+        }
+    }
+
+    /// Returns whether blending, coverage and color interpolation happen in linear light
+    fn linear_blending(&self) -> bool {
+        false
+    }
+
+    /// Returns the rectangle `x0, y0, x1, y1` of pixels that can be written, `x1` and `y1` excluded
+    ///
+    /// Geometric-Objects may skip pixels outside of it, the whole Canvas by default.
+    fn clip_rect(&self) -> (i32, i32, i32, i32) {
+        (0, 0, self.width(), self.height())
+    }
+
+    /// Tests a depth against the depth buffer of the Canvas and stores it if it is closer
+    ///
+    /// Canvases without depth buffer let every pixel pass.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - X-Coordinate of the pixel
+    /// * `y` - Y-Coordinate of the pixel
+    /// * `depth` - Depth of the new pixel, smaller values are closer to the viewer
+    ///
+    fn depth_test(&mut self, _x: i32, _y: i32, _depth: f64) -> bool {
+        true
+    }
+
+    /// Blends a color over a pixel if it passes the depth test
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - X-Coordinate of the pixel
+    /// * `y` - Y-Coordinate of the pixel
+    /// * `color` - Color to blend over the pixel
+    /// * `coverage` - Covered area of the pixel between 0..1, multiplies the alpha of `color`
+    /// * `depth` - Depth of the new pixel, smaller values are closer to the viewer
+    ///
+    fn blend_pixel_depth(&mut self, x: i32, y: i32, color: &Color, coverage: f64, depth: f64) {
+        if self.depth_test(x, y, depth) {
+            self.blend_pixel(x, y, color, coverage);
+        }
+    }
+
+    /// Blends a color over the samples of a pixel a coverage mask selects
+    ///
+    /// Canvases storing a single color per pixel blend by the share of selected samples.
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - X-Coordinate of the pixel
+    /// * `y` - Y-Coordinate of the pixel
+    /// * `color` - Color to blend over the samples
+    /// * `mask` - Bit `i` selects sample `i`
+    /// * `samples` - Number of samples per pixel
+    ///
+    fn blend_samples(&mut self, x: i32, y: i32, color: &Color, mask: u32, samples: usize) {
+        self.blend_pixel(x, y, color, mask.count_ones() as f64 / samples.max(1) as f64);
+    }
+
+    /// Draws into a transparent offscreen layer covering `clip_rect` and blends the layer over the Canvas as a whole
+    ///
+    /// The drawing keeps the coordinates and depth test of the Canvas. The default layer stores 8-bit colors,
+    /// Canvases storing colors more precisely draw the layer at their own precision.
+    ///
+    /// # Arguments
+    ///
+    /// * `opacity` - Opacity of the layer between 0..1, multiplies the alpha of every pixel
+    /// * `draw` - Draws into the layer
+    ///
+    fn draw_layer(&mut self, opacity: f64, draw: &mut dyn FnMut(&mut dyn Canvas)) {
+        let (x0, y0, x1, y1) = layer::writable_rect(self);
+        if x0 >= x1 || y0 >= y1 {
+            return;
+        }
+        let mut layer = Framebuffer::new(x1 - x0, y1 - y0);
+        layer.set_linear_blending(self.linear_blending());
+        let layer = layer::draw_offscreen(self, layer, x0, y0, draw);
+        for y in 0..layer.height() {
+            for x in 0..layer.width() {
+                let color = layer.get_pixel(x, y).unwrap();
+                if color.a > 0 {
+                    self.blend_pixel(x0 + x, y0 + y, &color, opacity);
+                }
+            }
+        }
+    }
+
+    /// Overwrites the horizontal span `x0..x1` of row `y`
+    ///
+    /// # Arguments
+    ///
+    /// * `x0` - First X-Coordinate of the span
+    /// * `x1` - X-Coordinate after the last pixel of the span
+    /// * `y` - Y-Coordinate of the span
+    /// * `color` - New color of the span
+    ///
+    fn fill_span(&mut self, x0: i32, x1: i32, y: i32, color: &Color) {
+        if y < 0 || y >= self.height() {
+            return;
+        }
+        for x in x0.max(0)..x1.min(self.width()) {
+            self.set_pixel(x, y, color);
+        }
+    }
+
+    /// Overwrites the horizontal span `x0..x1` of row `y` with two colors interpolated in sRGB
+    ///
+    /// # Arguments
+    ///
+    /// * `x0` - First X-Coordinate of the span
+    /// * `x1` - X-Coordinate after the last pixel of the span
+    /// * `y` - Y-Coordinate of the span
+    /// * `from` - Color at `t` = 0
+    /// * `to` - Color at `t` = 1
+    /// * `t` - Interpolation value at `x0` and its change per pixel, clamped to 0..1 per pixel
+    ///
+    fn fill_gradient_span(&mut self, x0: i32, x1: i32, y: i32, from: &Color, to: &Color, t: (f64, f64)) {
+        if y < 0 || y >= self.height() {
+            return;
+        }
+        for x in x0.max(0)..x1.min(self.width()) {
+            let t = (t.0 + (x - x0) as f64 * t.1).clamp(0.0, 1.0);
+            self.set_pixel(x, y, &interpolate_color(from, to, t, ColorSpace::Srgb));
+        }
+    }
+
+    /// Blends a color over the horizontal span `x0..x1` of row `y`
+    ///
+    /// # Arguments
+    ///
+    /// * `x0` - First X-Coordinate of the span
+    /// * `x1` - X-Coordinate after the last pixel of the span
+    /// * `y` - Y-Coordinate of the span
+    /// * `color` - Color to blend over the span
+    /// * `coverage` - Covered area of every pixel between 0..1, multiplies the alpha of `color`
+    ///
+    fn blend_span(&mut self, x0: i32, x1: i32, y: i32, color: &Color, coverage: f64) {
+        if y < 0 || y >= self.height() {
+            return;
+        }
+        for x in x0.max(0)..x1.min(self.width()) {
+            self.blend_pixel(x, y, color, coverage);
+        }
+    }
+
+    /// Blends a color over the pixels of the horizontal span `x0..x1` of row `y` passing the depth test
+    ///
+    /// The pixels passing are blended in runs with `blend_span`.
+    ///
+    /// # Arguments
+    ///
+    /// * `x0` - First X-Coordinate of the span
+    /// * `x1` - X-Coordinate after the last pixel of the span
+    /// * `y` - Y-Coordinate of the span
+    /// * `color` - Color to blend over the span
+    /// * `coverage` - Covered area of every pixel between 0..1, multiplies the alpha of `color`
+    /// * `depth` - Depth of the span, smaller values are closer to the viewer
+    ///
+    fn blend_span_depth(&mut self, x0: i32, x1: i32, y: i32, color: &Color, coverage: f64, depth: f64) {
+        let mut start = x0;
+        for x in x0..x1 {
+            if !self.depth_test(x, y, depth) {
+                if start < x {
+                    self.blend_span(start, x, y, color, coverage);
+                }
+                start = x + 1;
+            }
+        }
+        if start < x1 {
+            self.blend_span(start, x1, y, color, coverage);
+        }
+    }
+}
+
+/// Blends `src` over `dst` with straight alpha and returns the result
+///
+/// # Arguments
+///
+/// * `dst` - Color underneath
+/// * `src` - Color on top
+/// * `coverage` - Covered area between 0..1, multiplies the alpha of `src`
+///
+/// # Example
+///
+/// ```
+/// extern crate graphic_library;
+/// extern crate raster;
+/// use raster::Color;
+/// use graphic_library::graphic::blend;
+/// fn main() {
+/// //Half covered red over black
+/// let color = blend(&Color::rgb(0, 0, 0), &Color::rgb(255, 0, 0), 0.5);
+/// assert_eq!(128, color.r);
+/// }
+/// ```
+pub fn blend(dst: &Color, src: &Color, coverage: f64) -> Color {
+    let mut pixel = [dst.r, dst.g, dst.b, dst.a];
+    blend_bytes(&mut pixel, &[src.r, src.g, src.b, src.a], coverage);
+    Color::rgba(pixel[0], pixel[1], pixel[2], pixel[3])
+}
+
+/// Blends `src` over the pixel `dst` with straight alpha, both with alpha last and the other channels in the same order
+///
+/// Computes in `f32` with the operations of the vector kernels in `simd`, which blend eight pixels at once.
+pub(crate) fn blend_bytes(dst: &mut [u8], src: &[u8; 4], coverage: f64) {
+    let sa = (src[3] as f32 / 255.0) * coverage.clamp(0.0, 1.0) as f32;
+    let da = dst[3] as f32 / 255.0;
+    let oa = sa + da * (1.0 - sa);
+    if oa <= 0.0 {
+        dst.copy_from_slice(&[0, 0, 0, 0]);
+        return;
+    }
+    for (d, &s) in dst.iter_mut().zip(src).take(3) {
+        *d = ((s as f32 * sa + *d as f32 * da * (1.0 - sa)) / oa).round() as u8;
+    }
+    dst[3] = (oa * 255.0).round() as u8;
+}
+
+/// Blends `src` over `dst` in linear light with straight alpha and returns the sRGB encoded result
+///
+/// # Arguments
+///
+/// * `dst` - Color underneath
+/// * `src` - Color on top
+/// * `coverage` - Covered area between 0..1, multiplies the alpha of `src`
+///
+/// # Example
+///
+/// ```
+/// extern crate graphic_library;
+/// extern crate raster;
+/// use raster::Color;
+/// use graphic_library::graphic::blend_linear;
+/// fn main() {
+/// //Half covered white over black emits half the light
+/// let color = blend_linear(&Color::rgb(0, 0, 0), &Color::rgb(255, 255, 255), 0.5);
+/// assert_eq!(188, color.r);
+/// }
+/// ```
+pub fn blend_linear(dst: &Color, src: &Color, coverage: f64) -> Color {
+    let mut pixel = [dst.r, dst.g, dst.b, dst.a];
+    blend_linear_bytes(&mut pixel, &[src.r, src.g, src.b, src.a], coverage);
+    Color::rgba(pixel[0], pixel[1], pixel[2], pixel[3])
+}
+
+/// Blends `src` over the pixel `dst` in linear light, both with alpha last and the other channels in the same order
+pub(crate) fn blend_linear_bytes(dst: &mut [u8], src: &[u8; 4], coverage: f64) {
+    let sa = (src[3] as f32 / 255.0) * coverage.clamp(0.0, 1.0) as f32;
+    let da = dst[3] as f32 / 255.0;
+    let oa = sa + da * (1.0 - sa);
+    if oa <= 0.0 {
+        dst.copy_from_slice(&[0, 0, 0, 0]);
+        return;
+    }
+    for (d, &s) in dst.iter_mut().zip(src).take(3) {
+        *d = linear_to_srgb((srgb_to_linear(s) * sa + srgb_to_linear(*d) * da * (1.0 - sa)) / oa);
+    }
+    dst[3] = (oa * 255.0).round() as u8;
+}
+
+/// Blends `src` over `dst` with a blend mode and straight alpha
+///
+/// # Arguments
+///
+/// * `dst` - Color underneath
+/// * `src` - Color on top
+/// * `coverage` - Covered area between 0..1, multiplies the alpha of `src`
+/// * `mode` - Operation combining the colors where `src` covers `dst`
+///
+/// # Example
+///
+/// ```
+/// extern crate graphic_library;
+/// extern crate raster;
+/// use raster::Color;
+/// use graphic_library::graphic::{blend_with, BlendMode};
+/// fn main() {
+/// //Multiplying with red keeps only the red channel
+/// let color = blend_with(&Color::rgb(200, 200, 200), &Color::rgb(255, 0, 0), 1.0, BlendMode::Multiply);
+/// assert_eq!((200, 0, 0), (color.r, color.g, color.b));
+/// }
+/// ```
+pub fn blend_with(dst: &Color, src: &Color, coverage: f64, mode: BlendMode) -> Color {
+    let channel = |d: u8, s: u8| (mode.mix(d as f32 / 255.0, s as f32 / 255.0).clamp(0.0, 1.0) * 255.0).round() as u8;
+    let mixed = Color::rgba(channel(dst.r, src.r), channel(dst.g, src.g), channel(dst.b, src.b), src.a);
+    blend(dst, &mixed, coverage)
+}
+
+/// Blends `src` over `dst` with a blend mode in linear light and returns the sRGB encoded result
+///
+/// # Arguments
+///
+/// * `dst` - Color underneath
+/// * `src` - Color on top
+/// * `coverage` - Covered area between 0..1, multiplies the alpha of `src`
+/// * `mode` - Operation combining the colors where `src` covers `dst`
+///
+/// # Example
+///
+/// ```
+/// extern crate graphic_library;
+/// extern crate raster;
+/// use raster::Color;
+/// use graphic_library::graphic::{blend_with_linear, BlendMode};
+/// fn main() {
+/// //Screening two half bright grays emits three quarters of the light
+/// let color = blend_with_linear(&Color::rgb(188, 188, 188), &Color::rgb(188, 188, 188), 1.0, BlendMode::Screen);
+/// assert_eq!(225, color.r);
+/// }
+/// ```
+pub fn blend_with_linear(dst: &Color, src: &Color, coverage: f64, mode: BlendMode) -> Color {
+    let channel = |d: u8, s: u8| linear_to_srgb(mode.mix(srgb_to_linear(d), srgb_to_linear(s)));
+    let mixed = Color::rgba(channel(dst.r, src.r), channel(dst.g, src.g), channel(dst.b, src.b), src.a);
+    blend_linear(dst, &mixed, coverage)
+}
+
+impl Canvas for raster::Image {
+    fn width(&self) -> i32 {
+        self.width
+    }
+
+    fn height(&self) -> i32 {
+        self.height
+    }
+
+    fn get_pixel(&self, x: i32, y: i32) -> Option<Color> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return None;
+        }
+        raster::Image::get_pixel(self, x, y).ok()
+    }
+
+    fn set_pixel(&mut self, x: i32, y: i32, color: &Color) {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return;
+        }
+        raster::Image::set_pixel(self, x, y, color.clone()).unwrap();
+    }
+}
@@ -0,0 +1,336 @@
+extern crate raster;
+
+use raster::Color;
+use graphic::Canvas;
+
+/// Color space in which colors are interpolated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSpace {
+    /// Mixes the sRGB encoded channels directly
+    Srgb,
+    /// Mixes linear light, decoding and re-encoding sRGB
+    LinearRgb,
+    /// Mixes hue, saturation and value, hue takes the shorter way around the color wheel
+    Hsv,
+    /// Mixes hue, saturation and lightness, hue takes the shorter way around the color wheel
+    Hsl,
+    /// Mixes in CIE L*a*b* with a D65 white point
+    Lab,
+    /// Mixes in the perceptually uniform Oklab space
+    Oklab
+}
+
+impl ColorSpace {
+    /// Returns the color space a Canvas blends in, `LinearRgb` if it blends linear light
+    pub fn for_canvas(canvas: &dyn Canvas) -> ColorSpace {
+        if canvas.linear_blending() {
+            ColorSpace::LinearRgb
+        } else {
+            ColorSpace::Srgb
+        }
+    }
+
+    /// Returns the three color channels in this space, alpha is kept separately
+    fn decode(self, color: &Color) -> [f64; 3] {
+        let srgb = [color.r as f64 / 255.0, color.g as f64 / 255.0, color.b as f64 / 255.0];
+        let linear = || [srgb_to_linear(color.r) as f64, srgb_to_linear(color.g) as f64, srgb_to_linear(color.b) as f64];
+        match self {
+            ColorSpace::Srgb => srgb,
+            ColorSpace::LinearRgb => linear(),
+            ColorSpace::Hsv => rgb_to_hsv(srgb),
+            ColorSpace::Hsl => rgb_to_hsl(srgb),
+            ColorSpace::Lab => linear_to_lab(linear()),
+            ColorSpace::Oklab => linear_to_oklab(linear())
+        }
+    }
+
+    fn encode(self, v: [f64; 3], alpha: f64) -> Color {
+        let byte = |c: f64| (c * 255.0).round().clamp(0.0, 255.0) as u8;
+        let srgb = |c: [f64; 3]| Color::rgba(byte(c[0]), byte(c[1]), byte(c[2]), alpha.round().clamp(0.0, 255.0) as u8);
+        let linear = |c: [f64; 3]| Color::rgba(linear_to_srgb(c[0] as f32), linear_to_srgb(c[1] as f32), linear_to_srgb(c[2] as f32), alpha.round().clamp(0.0, 255.0) as u8);
+        match self {
+            ColorSpace::Srgb => srgb(v),
+            ColorSpace::LinearRgb => linear(v),
+            ColorSpace::Hsv => srgb(hsv_to_rgb(v)),
+            ColorSpace::Hsl => srgb(hsl_to_rgb(v)),
+            ColorSpace::Lab => linear(lab_to_linear(v)),
+            ColorSpace::Oklab => linear(oklab_to_linear(v))
+        }
+    }
+
+    fn has_hue(self) -> bool {
+        self == ColorSpace::Hsv || self == ColorSpace::Hsl
+    }
+
+    /// Returns the weighted sum of up to three colors, hues are summed as offsets to the first chromatic hue
+    fn mix(self, colors: &[(&Color, f64)]) -> Color {
+        //Runs per pixel, so the colors are decoded on the stack
+        let mut decoded = [[0.0; 3]; 3];
+        for (d, &(c, _)) in decoded.iter_mut().zip(colors) {
+            *d = self.decode(c);
+        }
+        let decoded = &decoded[..colors.len()];
+        let reference = decoded.iter().find(|d| d[1] > 0.0).map_or(0.0, |d| d[0]);
+        let mut v = [0.0; 3];
+        let mut alpha = 0.0;
+        for (d, &(c, w)) in decoded.iter().zip(colors) {
+            if self.has_hue() {
+                // Achromatic colors have no hue of their own, they take the reference hue
+                let offset = if d[1] > 0.0 { d[0] - reference } else { 0.0 };
+                v[0] += w * (offset - 360.0 * (offset / 360.0).round());
+            } else {
+                v[0] += w * d[0];
+            }
+            v[1] += w * d[1];
+            v[2] += w * d[2];
+            alpha += w * c.a as f64;
+        }
+        if self.has_hue() {
+            v[0] = (reference + v[0]).rem_euclid(360.0);
+        }
+        self.encode(v, alpha)
+    }
+}
+
+fn rgb_to_hsv(c: [f64; 3]) -> [f64; 3] {
+    let max = c[0].max(c[1]).max(c[2]);
+    let min = c[0].min(c[1]).min(c[2]);
+    let s = if max > 0.0 { (max - min) / max } else { 0.0 };
+    [hue(c, max, min), s, max]
+}
+
+fn hsv_to_rgb(c: [f64; 3]) -> [f64; 3] {
+    let chroma = c[2] * c[1];
+    from_hue(c[0], chroma, c[2] - chroma)
+}
+
+fn rgb_to_hsl(c: [f64; 3]) -> [f64; 3] {
+    let max = c[0].max(c[1]).max(c[2]);
+    let min = c[0].min(c[1]).min(c[2]);
+    let l = (max + min) / 2.0;
+    let s = if max == min { 0.0 } else { (max - min) / (1.0 - (2.0 * l - 1.0).abs()) };
+    [hue(c, max, min), s, l]
+}
+
+fn hsl_to_rgb(c: [f64; 3]) -> [f64; 3] {
+    let chroma = (1.0 - (2.0 * c[2] - 1.0).abs()) * c[1];
+    from_hue(c[0], chroma, c[2] - chroma / 2.0)
+}
+
+/// Returns the hue in degrees of an RGB color with given maximum and minimum channel
+fn hue(c: [f64; 3], max: f64, min: f64) -> f64 {
+    let d = max - min;
+    if d <= 0.0 {
+        return 0.0;
+    }
+    let h = if max == c[0] {
+        (c[1] - c[2]) / d
+    } else if max == c[1] {
+        (c[2] - c[0]) / d + 2.0
+    } else {
+        (c[0] - c[1]) / d + 4.0
+    };
+    (h * 60.0).rem_euclid(360.0)
+}
+
+/// Returns the RGB color of a hue in degrees with given chroma, lifted by `m`
+fn from_hue(h: f64, chroma: f64, m: f64) -> [f64; 3] {
+    let h = h.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as i32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x)
+    };
+    [r + m, g + m, b + m]
+}
+
+const LAB_EPSILON: f64 = 216.0 / 24389.0;
+const LAB_KAPPA: f64 = 24389.0 / 27.0;
+const D65: [f64; 3] = [0.95047, 1.0, 1.08883];
+
+fn linear_to_lab(c: [f64; 3]) -> [f64; 3] {
+    let xyz = [0.412_456_4 * c[0] + 0.357_576_1 * c[1] + 0.180_437_5 * c[2],
+               0.212_672_9 * c[0] + 0.715_152_2 * c[1] + 0.072_175_0 * c[2],
+               0.019_333_9 * c[0] + 0.119_192_0 * c[1] + 0.950_304_1 * c[2]];
+    let f = |t: f64| if t > LAB_EPSILON { t.cbrt() } else { (LAB_KAPPA * t + 16.0) / 116.0 };
+    let fx = f(xyz[0] / D65[0]);
+    let fy = f(xyz[1] / D65[1]);
+    let fz = f(xyz[2] / D65[2]);
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+fn lab_to_linear(c: [f64; 3]) -> [f64; 3] {
+    let fy = (c[0] + 16.0) / 116.0;
+    let fx = fy + c[1] / 500.0;
+    let fz = fy - c[2] / 200.0;
+    let f = |t: f64| if t * t * t > LAB_EPSILON { t * t * t } else { (116.0 * t - 16.0) / LAB_KAPPA };
+    let x = f(fx) * D65[0];
+    let y = if c[0] > LAB_KAPPA * LAB_EPSILON { fy * fy * fy } else { c[0] / LAB_KAPPA } * D65[1];
+    let z = f(fz) * D65[2];
+    [3.240_454_2 * x - 1.537_138_5 * y - 0.498_531_4 * z,
+     -0.969_266_0 * x + 1.876_010_8 * y + 0.041_556_0 * z,
+     0.055_643_4 * x - 0.204_025_9 * y + 1.057_225_2 * z]
+}
+
+fn linear_to_oklab(c: [f64; 3]) -> [f64; 3] {
+    let l = (0.412_221_470_8 * c[0] + 0.536_332_536_3 * c[1] + 0.051_445_992_9 * c[2]).cbrt();
+    let m = (0.211_903_498_2 * c[0] + 0.680_699_545_1 * c[1] + 0.107_396_956_6 * c[2]).cbrt();
+    let s = (0.088_302_461_9 * c[0] + 0.281_718_837_6 * c[1] + 0.629_978_700_5 * c[2]).cbrt();
+    [0.210_454_255_3 * l + 0.793_617_785_0 * m - 0.004_072_046_8 * s,
+     1.977_998_495_1 * l - 2.428_592_205_0 * m + 0.450_593_709_9 * s,
+     0.025_904_037_1 * l + 0.782_771_766_2 * m - 0.808_675_766_0 * s]
+}
+
+fn oklab_to_linear(c: [f64; 3]) -> [f64; 3] {
+    let l = c[0] + 0.396_337_777_4 * c[1] + 0.215_803_757_3 * c[2];
+    let m = c[0] - 0.105_561_345_8 * c[1] - 0.063_854_172_8 * c[2];
+    let s = c[0] - 0.089_484_177_5 * c[1] - 1.291_485_548_0 * c[2];
+    let (l, m, s) = (l * l * l, m * m * m, s * s * s);
+    [4.076_741_662_1 * l - 3.307_711_591_3 * m + 0.230_969_929_2 * s,
+     -1.268_438_004_6 * l + 2.609_757_401_1 * m - 0.341_319_396_5 * s,
+     -0.004_196_086_3 * l - 0.703_418_614_7 * m + 1.707_614_701_0 * s]
+}
+
+/// Linearly interpolates two colors together
+///
+/// # Arguments
+///
+/// * `a` - 1st color to interpolate
+/// * `b` - 2nd color to interpolate
+/// * `t` - Percentage Value between 0..1
+/// * `space` - Color space the interpolation happens in
+///
+/// # Example
+///
+/// ```
+/// extern crate graphic_library;
+/// extern crate raster;
+/// use raster::Color;
+/// use graphic_library::graphic::{ColorSpace, interpolate_color};
+/// fn main() {
+/// //Halfway between black and white in linear light is a light gray
+/// let gray = interpolate_color(&Color::black(), &Color::white(), 0.5, ColorSpace::LinearRgb);
+/// assert_eq!(188, gray.r);
+/// }
+/// ```
+pub fn interpolate_color(a: &Color, b: &Color, t: f64, space: ColorSpace) -> Color {
+    space.mix(&[(a, 1.0 - t), (b, t)])
+}
+
+/// Barycentricly interpolates three colors together
+/// Condition: alpha + beta + gamma == 1
+/// # Arguments
+///
+/// * `a` - 1st color to interpolate
+/// * `b` - 2nd color to interpolate
+/// * `c` - 3rd color to interpolate
+/// * `alpha` - Percentage Value between 0..1
+/// * `beta` - Percentage Value between 0..1
+/// * `gamma` - Percentage Value between 0..1
+/// * `space` - Color space the interpolation happens in
+///
+pub fn interpolate_color_barycentric(a: &Color, b: &Color, c: &Color, alpha: f64, beta: f64, gamma: f64, space: ColorSpace) -> Color {
+    space.mix(&[(a, alpha), (b, beta), (c, gamma)])
+}
+
+/// Converts an 8-bit sRGB encoded channel into linear light between 0..1
+///
+/// # Arguments
+///
+/// * `v` - sRGB encoded channel
+///
+/// # Example
+///
+/// ```
+/// extern crate graphic_library;
+/// use graphic_library::graphic::srgb_to_linear;
+/// fn main() {
+/// //returns roughly 0.214
+/// let linear = srgb_to_linear(128);
+/// }
+/// ```
+pub fn srgb_to_linear(v: u8) -> f32 {
+    let c = v as f32 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Converts a linear light channel into an 8-bit sRGB encoded channel, values outside of 0..1 are clamped
+///
+/// # Arguments
+///
+/// * `v` - Linear light channel
+///
+/// # Example
+///
+/// ```
+/// extern crate graphic_library;
+/// use graphic_library::graphic::linear_to_srgb;
+/// fn main() {
+/// //returns 188
+/// let encoded = linear_to_srgb(0.5);
+/// }
+/// ```
+pub fn linear_to_srgb(v: f32) -> u8 {
+    let c = v.clamp(0.0, 1.0);
+    let s = if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    (s * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn srgb_round_trip() {
+        for v in 0..256 {
+            assert_eq!(v as u8, linear_to_srgb(srgb_to_linear(v as u8)));
+        }
+    }
+
+    #[test]
+    fn interpolate_linear_rgb() {
+        let color = interpolate_color(&Color::rgb(255, 0, 0), &Color::rgb(0, 255, 0), 0.5, ColorSpace::LinearRgb);
+        assert_eq!((188, 188, 0, 255), (color.r, color.g, color.b, color.a));
+        let color = interpolate_color(&Color::rgb(255, 0, 0), &Color::rgb(0, 255, 0), 0.5, ColorSpace::Srgb);
+        assert_eq!((128, 128, 0, 255), (color.r, color.g, color.b, color.a));
+    }
+
+    #[test]
+    fn round_trips() {
+        let colors = [Color::rgb(200, 100, 30), Color::rgb(0, 0, 0), Color::rgb(255, 255, 255), Color::rgb(12, 240, 99)];
+        for space in &[ColorSpace::Srgb, ColorSpace::LinearRgb, ColorSpace::Hsv, ColorSpace::Hsl, ColorSpace::Lab, ColorSpace::Oklab] {
+            for color in &colors {
+                let mixed = interpolate_color(color, color, 0.3, *space);
+                assert_eq!((color.r, color.g, color.b), (mixed.r, mixed.g, mixed.b), "{:?}", space);
+            }
+        }
+    }
+
+    #[test]
+    fn hue_takes_shortest_path() {
+        //Magenta-red (hue 330) to orange (hue 30) passes red, not green
+        let color = interpolate_color(&Color::rgb(255, 0, 128), &Color::rgb(255, 128, 0), 0.5, ColorSpace::Hsv);
+        assert_eq!((255, 0, 0), (color.r, color.g, color.b));
+        //Gray has no hue and must not pull the hue towards red
+        let color = interpolate_color(&Color::rgb(128, 128, 128), &Color::rgb(0, 0, 255), 0.5, ColorSpace::Hsl);
+        assert!(color.b > color.r && color.b > color.g);
+    }
+
+    #[test]
+    fn oklab_midpoint() {
+        let color = interpolate_color(&Color::black(), &Color::white(), 0.5, ColorSpace::Oklab);
+        assert_eq!((99, 99, 99), (color.r, color.g, color.b));
+    }
+}
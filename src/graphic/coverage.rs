@@ -0,0 +1,391 @@
+extern crate raster;
+
+use raster::Color;
+use geometric::BoundingBox;
+use graphic::Canvas;
+
+/// Rows summed up at once, the accumulation buffer holds this many rows of the rasterizer
+const BAND: usize = 16;
+
+/// Exact-area rasterizer for filled paths
+///
+/// Every edge adds the signed area it covers to an accumulation buffer, summing up a row from left to
+/// right then yields how much of each pixel lies inside the path. Edges of arbitrary closed paths can be
+/// added in any order, overlapping parts are filled once.
+/// The buffer only spans a band of rows which is filled from the edges one band after the other,
+/// so memory grows with the width and the number of edges, not the area.
+#[derive(Debug, Clone)]
+pub struct CoverageRasterizer {
+    /// X-Coordinate of the leftmost pixel
+    x: i32,
+    /// Y-Coordinate of the topmost pixel
+    y: i32,
+    width: usize,
+    height: usize,
+    /// Edges flattened into the columns of the rasterizer, relative to its top left pixel
+    edges: Vec<Edge>
+}
+
+/// Part of an edge within the columns of a rasterizer, going downwards
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    x0: f64,
+    y0: f64,
+    x1: f64,
+    y1: f64,
+    /// 1 for edges going down, -1 for edges going up
+    dir: f64
+}
+
+impl CoverageRasterizer {
+    /// Returns an empty rasterizer for the pixels from (x0,y0) up to but excluding (x1,y1)
+    ///
+    /// # Arguments
+    ///
+    /// * `x0` - X-Coordinate of the leftmost pixel
+    /// * `y0` - Y-Coordinate of the topmost pixel
+    /// * `x1` - X-Coordinate right of the rightmost pixel
+    /// * `y1` - Y-Coordinate below the lowest pixel
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// use graphic_library::graphic::CoverageRasterizer;
+    /// fn main(){
+    /// //Half of the pixel (1,0) lies inside the rectangle
+    /// let mut rasterizer = CoverageRasterizer::new(0, 0, 4, 4);
+    /// rasterizer.polygon(&[(0.0, 0.0), (1.5, 0.0), (1.5, 1.0), (0.0, 1.0)]);
+    /// assert_eq!(0.5, rasterizer.coverage(1, 0));
+    /// }
+    /// ```
+    pub fn new(x0: i32, y0: i32, x1: i32, y1: i32) -> CoverageRasterizer {
+        CoverageRasterizer {
+            x: x0,
+            y: y0,
+            width: (x1 - x0).max(0) as usize,
+            height: (y1 - y0).max(0) as usize,
+            edges: Vec::new()
+        }
+    }
+
+    /// Returns an empty rasterizer for the pixels of a Canvas a bounding box may touch
+    ///
+    /// # Arguments
+    ///
+    /// * `canvas` - Canvas the coverage is drawn into, pixels outside of its clipping rectangle are skipped
+    /// * `bounds` - Bounding box of the path
+    ///
+    pub fn for_canvas(canvas: &dyn Canvas, bounds: &BoundingBox) -> CoverageRasterizer {
+        let (x0, y0, x1, y1) = canvas.clip_rect();
+        CoverageRasterizer::new((bounds.min_x.floor() as i32).max(x0.max(0)),
+                                (bounds.min_y.floor() as i32).max(y0.max(0)),
+                                (bounds.max_x.ceil() as i32).min(x1.min(canvas.width())),
+                                (bounds.max_y.ceil() as i32).min(y1.min(canvas.height())))
+    }
+
+    /// Adds a directed edge of a path, edges with coordinates that are not finite are skipped
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - Start of the edge
+    /// * `to` - End of the edge
+    ///
+    pub fn line(&mut self, from: (f64, f64), to: (f64, f64)) {
+        if !(from.0.is_finite() && from.1.is_finite() && to.0.is_finite() && to.1.is_finite()) {
+            return;
+        }
+        let (x0, y0) = (from.0 - self.x as f64, from.1 - self.y as f64);
+        let (x1, y1) = (to.0 - self.x as f64, to.1 - self.y as f64);
+        let width = self.width as f64;
+        //Parts left or right of the rasterizer still count for the pixels beside them, so they are
+        //split off and flattened onto the border
+        let mut t = [0.0, 1.0, 0.0, 1.0];
+        if x0 != x1 {
+            t[2] = (-x0 / (x1 - x0)).clamp(0.0, 1.0);
+            t[3] = ((width - x0) / (x1 - x0)).clamp(0.0, 1.0);
+        }
+        t.sort_by(f64::total_cmp);
+        for pair in t.windows(2) {
+            if pair[1] > pair[0] {
+                let at = |t: f64| ((x0 + (x1 - x0) * t).clamp(0.0, width), y0 + (y1 - y0) * t);
+                self.add_edge(at(pair[0]), at(pair[1]));
+            }
+        }
+    }
+
+    /// Keeps an edge lying horizontally within the rasterizer if it crosses any of its rows
+    fn add_edge(&mut self, from: (f64, f64), to: (f64, f64)) {
+        if from.1 == to.1 {
+            return;
+        }
+        let (dir, (x0, y0), (x1, y1)) = if from.1 < to.1 { (1.0, from, to) } else { (-1.0, to, from) };
+        if y1 > 0.0 && y0 < self.height as f64 {
+            self.edges.push(Edge { x0, y0, x1, y1, dir });
+        }
+    }
+
+    /// Adds a closed polygon, polygons with coordinates that are not finite are skipped
+    ///
+    /// # Arguments
+    ///
+    /// * `points` - Corners of the polygon, the last one is connected to the first one
+    ///
+    pub fn polygon(&mut self, points: &[(f64, f64)]) {
+        if points.iter().any(|&(x, y)| !(x.is_finite() && y.is_finite())) {
+            return;
+        }
+        for (i, &from) in points.iter().enumerate() {
+            self.line(from, points[(i + 1) % points.len()]);
+        }
+    }
+
+    /// Removes all edges
+    pub fn clear(&mut self) {
+        self.edges.clear();
+    }
+
+    /// Returns how much of a pixel lies inside the path between 0..1
+    pub fn coverage(&self, x: i32, y: i32) -> f64 {
+        let (x, y) = (x - self.x, y - self.y);
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return 0.0;
+        }
+        let mut cells = vec![0.0; self.width + 2];
+        for edge in &self.edges {
+            self.accumulate(&mut cells, edge, y as usize, y as usize + 1);
+        }
+        let area: f64 = cells[..=x as usize].iter().sum();
+        area.abs().min(1.0)
+    }
+
+    /// Calls `f` with the index and the signed area per cell of every row, band by band
+    fn for_each_row<F: FnMut(usize, &[f64])>(&self, mut f: F) {
+        if self.width == 0 {
+            return;
+        }
+        let stride = self.width + 2;
+        let mut edges: Vec<&Edge> = self.edges.iter().collect();
+        edges.sort_by(|a, b| a.y0.total_cmp(&b.y0));
+        let mut next = 0;
+        let mut active: Vec<&Edge> = Vec::new();
+        let mut cells = vec![0.0; stride * BAND.min(self.height)];
+        for top in (0..self.height).step_by(BAND) {
+            let bottom = (top + BAND).min(self.height);
+            while next < edges.len() && edges[next].y0 < bottom as f64 {
+                active.push(edges[next]);
+                next += 1;
+            }
+            active.retain(|edge| edge.y1 > top as f64);
+            cells.fill(0.0);
+            for edge in &active {
+                self.accumulate(&mut cells, edge, top, bottom);
+            }
+            for (row, cells) in cells.chunks(stride).take(bottom - top).enumerate() {
+                f(top + row, cells);
+            }
+        }
+    }
+
+    /// Calls `f` with the coordinates and the coverage of every pixel touched by the path
+    pub fn for_each<F: FnMut(i32, i32, f64)>(&self, mut f: F) {
+        let width = self.width;
+        self.for_each_row(|y, row| {
+            let mut area = 0.0;
+            for (x, cell) in row[..width].iter().enumerate() {
+                area += cell;
+                //Ignores the rounding noise left over right of an edge
+                let coverage = area.abs().min(1.0);
+                if coverage > 1e-6 {
+                    f(self.x + x as i32, self.y + y as i32, coverage);
+                }
+            }
+        });
+    }
+
+    /// Calls `f` with the row, the pixels `x0..x1` and the coverage of every run of equally covered pixels
+    ///
+    /// Fully covered pixels report a coverage of exactly 1, so the inside of a path forms a single run.
+    pub fn for_each_span<F: FnMut(i32, i32, i32, f64)>(&self, mut f: F) {
+        self.for_each_row(|y, row| {
+            let y = self.y + y as i32;
+            let mut area = 0.0;
+            let mut run: Option<(i32, f64)> = None;
+            for (x, cell) in row[..self.width].iter().enumerate() {
+                let x = self.x + x as i32;
+                area += cell;
+                //Ignores the rounding noise left over right of an edge and within the inside
+                let mut coverage = area.abs().min(1.0);
+                if coverage > 1.0 - 1e-9 {
+                    coverage = 1.0;
+                }
+                if let Some((start, value)) = run {
+                    if value == coverage {
+                        continue;
+                    }
+                    f(start, x, y, value);
+                    run = None;
+                }
+                if coverage > 1e-6 {
+                    run = Some((x, coverage));
+                }
+            }
+            if let Some((start, value)) = run {
+                f(start, self.x + self.width as i32, y, value);
+            }
+        });
+    }
+
+    /// Blends a color into every pixel touched by the path, weighted by its coverage
+    ///
+    /// # Arguments
+    ///
+    /// * `canvas` - Canvas to draw into
+    /// * `color` - Color to fill with
+    /// * `depth` - Depth of the path, pixels failing the depth test of the Canvas are skipped
+    ///
+    pub fn fill(&self, canvas: &mut dyn Canvas, color: &Color, depth: f64) {
+        self.for_each_span(|x0, x1, y, coverage| canvas.blend_span_depth(x0, x1, y, color, coverage, depth));
+    }
+
+    /// Adds the signed area of an edge within the rows `first..end` to the cells of these rows
+    fn accumulate(&self, cells: &mut [f64], edge: &Edge, first: usize, end: usize) {
+        let (x0, y0, x1, y1) = (edge.x0, edge.y0, edge.x1, edge.y1);
+        let top = y0.max(first as f64);
+        let bottom = y1.min(end as f64);
+        if top >= bottom {
+            return;
+        }
+        let dxdy = (x1 - x0) / (y1 - y0);
+        let width = self.width as f64;
+        let stride = self.width + 2;
+        let mut x = (x0 + (top - y0) * dxdy).clamp(0.0, width);
+        for row in top.floor() as usize..bottom.ceil() as usize {
+            let dy = ((row + 1) as f64).min(bottom) - (row as f64).max(top);
+            let x_next = (x + dxdy * dy).clamp(0.0, width);
+            let d = dy * edge.dir;
+            let cells = &mut cells[(row - first) * stride..(row - first + 1) * stride];
+            let (left, right) = if x < x_next { (x, x_next) } else { (x_next, x) };
+            let left_floor = left.floor();
+            let l = left_floor as usize;
+            let right_ceil = right.ceil();
+            let r = right_ceil as usize;
+            if r <= l + 1 {
+                //The edge stays within one pixel, the part right of it is covered
+                let mid = 0.5 * (x + x_next) - left_floor;
+                cells[l] += d - d * mid;
+                cells[l + 1] += d * mid;
+            } else {
+                //The edge crosses several pixels, each gets the trapezoid left of it
+                let s = 1.0 / (right - left);
+                let left_fract = left - left_floor;
+                let first = 0.5 * s * (1.0 - left_fract) * (1.0 - left_fract);
+                let right_fract = right - right_ceil + 1.0;
+                let last = 0.5 * s * right_fract * right_fract;
+                cells[l] += d * first;
+                if r == l + 2 {
+                    cells[l + 1] += d * (1.0 - first - last);
+                } else {
+                    let second = s * (1.5 - left_fract);
+                    cells[l + 1] += d * (second - first);
+                    for cell in &mut cells[l + 2..r - 1] {
+                        *cell += d * s;
+                    }
+                    let before_last = second + (r - l - 3) as f64 * s;
+                    cells[r - 1] += d * (1.0 - before_last - last);
+                }
+                cells[r] += d * last;
+            }
+            x = x_next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(expected: f64, actual: f64) {
+        assert!((expected - actual).abs() < 1e-9, "{} != {}", expected, actual);
+    }
+
+    fn total(rasterizer: &CoverageRasterizer) -> f64 {
+        let mut sum = 0.0;
+        rasterizer.for_each(|_, _, coverage| sum += coverage);
+        sum
+    }
+
+    #[test]
+    fn summed_coverage_is_the_area() {
+        let mut rasterizer = CoverageRasterizer::new(0, 0, 16, 16);
+        rasterizer.polygon(&[(1.3, 2.7), (14.2, 4.1), (6.6, 13.9)]);
+        let area = 0.5 * ((14.2 - 1.3) * (13.9 - 2.7) - (6.6 - 1.3) * (4.1 - 2.7));
+        assert_close(area, total(&rasterizer));
+        //A diagonal halves the pixels it crosses
+        rasterizer.clear();
+        rasterizer.polygon(&[(0.0, 0.0), (4.0, 4.0), (0.0, 4.0)]);
+        assert_close(0.5, rasterizer.coverage(2, 2));
+        assert_close(1.0, rasterizer.coverage(0, 3));
+        assert_close(0.0, rasterizer.coverage(3, 0));
+    }
+
+    #[test]
+    fn clips_without_losing_coverage() {
+        //Both rasterizers see the same square, the second one only the right half of it
+        let square = [(-3.5, 1.0), (6.5, 1.0), (6.5, 3.0), (-3.5, 3.0)];
+        let mut full = CoverageRasterizer::new(-4, 0, 8, 4);
+        full.polygon(&square);
+        let mut clipped = CoverageRasterizer::new(2, 0, 4, 4);
+        clipped.polygon(&square);
+        for y in 0..4 {
+            for x in 2..4 {
+                assert_close(full.coverage(x, y), clipped.coverage(x, y));
+            }
+        }
+        assert_close(4.0, total(&clipped));
+        //The winding order does not matter
+        let mut reversed = CoverageRasterizer::new(-4, 0, 8, 4);
+        let mut points = square.to_vec();
+        points.reverse();
+        reversed.polygon(&points);
+        assert_close(20.0, total(&reversed));
+        assert_close(0.5, reversed.coverage(6, 1));
+    }
+
+    #[test]
+    fn skips_edges_that_are_not_finite() {
+        let mut rasterizer = CoverageRasterizer::new(0, 0, 4, 4);
+        rasterizer.polygon(&[(f64::NAN, 0.0), (3.0, 0.0), (3.0, 3.0)]);
+        rasterizer.line((0.0, 0.0), (1.0, f64::INFINITY));
+        assert_close(0.0, total(&rasterizer));
+    }
+
+    #[test]
+    fn bands_meet_without_seams() {
+        //A triangle spanning several bands of rows
+        let mut rasterizer = CoverageRasterizer::new(0, 0, 8, 3 * BAND as i32);
+        let height = 3.0 * BAND as f64 - 0.5;
+        rasterizer.polygon(&[(0.5, 0.5), (7.5, 0.5), (0.5, height)]);
+        assert_close(0.5 * 7.0 * (height - 0.5), total(&rasterizer));
+        assert_close(1.0, rasterizer.coverage(1, BAND as i32));
+    }
+
+    #[test]
+    fn spans_match_the_pixels() {
+        let mut rasterizer = CoverageRasterizer::new(0, 0, 16, 16);
+        rasterizer.polygon(&[(1.3, 2.7), (14.2, 4.1), (6.6, 13.9)]);
+        let mut pixels = Vec::new();
+        rasterizer.for_each(|x, y, coverage| pixels.push((x, y, coverage)));
+        let mut spans = Vec::new();
+        rasterizer.for_each_span(|x0, x1, y, coverage| spans.extend((x0..x1).map(|x| (x, y, coverage))));
+        assert_eq!(pixels.len(), spans.len());
+        for (pixel, span) in pixels.iter().zip(&spans) {
+            assert_eq!((pixel.0, pixel.1), (span.0, span.1));
+            assert!((pixel.2 - span.2).abs() < 1e-6);
+        }
+        //The inside of a row is a single span
+        let mut count = 0;
+        rasterizer.for_each_span(|_, _, y, coverage| count += (y == 8 && coverage == 1.0) as i32);
+        assert_eq!(1, count);
+    }
+}
@@ -0,0 +1,214 @@
+extern crate raster;
+
+use std::f64;
+use raster::Color;
+use graphic::Canvas;
+
+/// Per-pixel depth storage, a pixel passes the depth test if it is closer than the stored depth
+#[derive(Debug, Clone)]
+pub struct DepthBuffer {
+    width: i32,
+    height: i32,
+    data: Vec<f64>
+}
+
+impl DepthBuffer {
+    /// Returns a DepthBuffer where every pixel passes the first depth test
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - Width in pixels
+    /// * `height` - Height in pixels
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// use graphic_library::graphic::DepthBuffer;
+    /// fn main(){
+    /// let depth = DepthBuffer::new(640, 480);
+    /// }
+    /// ```
+    pub fn new(width: i32, height: i32) -> DepthBuffer {
+        let width = width.max(0);
+        let height = height.max(0);
+        DepthBuffer {
+            width,
+            height,
+            data: vec![f64::INFINITY; width as usize * height as usize]
+        }
+    }
+
+    /// Resets every pixel, so the next depth test passes
+    pub fn clear(&mut self) {
+        for depth in self.data.iter_mut() {
+            *depth = f64::INFINITY;
+        }
+    }
+
+    /// Overwrites the depths at `x, y` with the depths of a rectangle of another DepthBuffer
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - X-Coordinate the rectangle is copied to
+    /// * `y` - Y-Coordinate the rectangle is copied to
+    /// * `src` - DepthBuffer to copy from
+    /// * `rect` - Rectangle of `src` as `(x0, y0, x1, y1)`, it has to fit into both DepthBuffers
+    ///
+    #[cfg(feature = "parallel")]
+    pub(crate) fn copy_rect(&mut self, x: i32, y: i32, src: &DepthBuffer, rect: (i32, i32, i32, i32)) {
+        let (x0, y0, x1, y1) = rect;
+        let len = (x1 - x0).max(0) as usize;
+        for row in 0..y1 - y0 {
+            let start = (y + row) as usize * self.width as usize + x as usize;
+            let src_start = (y0 + row) as usize * src.width as usize + x0 as usize;
+            self.data[start..start + len].copy_from_slice(&src.data[src_start..src_start + len]);
+        }
+    }
+
+    /// Returns the stored depth of a pixel or `None` if it lies outside of the DepthBuffer
+    pub fn get(&self, x: i32, y: i32) -> Option<f64> {
+        self.offset(x, y).map(|i| self.data[i])
+    }
+
+    /// Overwrites the stored depth of a pixel, pixels outside of the DepthBuffer are ignored
+    pub fn set(&mut self, x: i32, y: i32, depth: f64) {
+        if let Some(i) = self.offset(x, y) {
+            self.data[i] = depth;
+        }
+    }
+
+    /// Stores a depth if it is closer than the stored one and returns whether it was
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - X-Coordinate of the pixel
+    /// * `y` - Y-Coordinate of the pixel
+    /// * `depth` - Depth of the new pixel, smaller values are closer to the viewer
+    ///
+    pub fn test(&mut self, x: i32, y: i32, depth: f64) -> bool {
+        match self.offset(x, y) {
+            Some(i) if depth < self.data[i] => {
+                self.data[i] = depth;
+                true
+            }
+            _ => false
+        }
+    }
+
+    fn offset(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            None
+        } else {
+            Some(y as usize * self.width as usize + x as usize)
+        }
+    }
+}
+
+/// Canvas drawing into another Canvas while depth testing against a DepthBuffer
+pub struct DepthCanvas<'a> {
+    canvas: &'a mut dyn Canvas,
+    depth: &'a mut DepthBuffer
+}
+
+impl<'a> DepthCanvas<'a> {
+    /// Returns a Canvas that only passes pixels closer than the ones already drawn
+    ///
+    /// # Arguments
+    ///
+    /// * `canvas` - Canvas receiving the pixels passing the depth test
+    /// * `depth` - DepthBuffer of the same size as `canvas`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// use graphic_library::graphic::{DepthBuffer, DepthCanvas, Framebuffer};
+    /// fn main(){
+    /// let mut framebuffer = Framebuffer::new(640, 480);
+    /// let mut depth = DepthBuffer::new(640, 480);
+    /// let canvas = DepthCanvas::new(&mut framebuffer, &mut depth);
+    /// }
+    /// ```
+    pub fn new(canvas: &'a mut dyn Canvas, depth: &'a mut DepthBuffer) -> DepthCanvas<'a> {
+        DepthCanvas { canvas, depth }
+    }
+}
+
+impl<'a> Canvas for DepthCanvas<'a> {
+    fn width(&self) -> i32 {
+        self.canvas.width()
+    }
+
+    fn height(&self) -> i32 {
+        self.canvas.height()
+    }
+
+    fn get_pixel(&self, x: i32, y: i32) -> Option<Color> {
+        self.canvas.get_pixel(x, y)
+    }
+
+    fn set_pixel(&mut self, x: i32, y: i32, color: &Color) {
+        self.canvas.set_pixel(x, y, color);
+    }
+
+    fn blend_pixel(&mut self, x: i32, y: i32, color: &Color, coverage: f64) {
+        self.canvas.blend_pixel(x, y, color, coverage);
+    }
+
+    fn linear_blending(&self) -> bool {
+        self.canvas.linear_blending()
+    }
+
+    fn clip_rect(&self) -> (i32, i32, i32, i32) {
+        self.canvas.clip_rect()
+    }
+
+    fn depth_test(&mut self, x: i32, y: i32, depth: f64) -> bool {
+        self.depth.test(x, y, depth)
+    }
+
+    fn blend_samples(&mut self, x: i32, y: i32, color: &Color, mask: u32, samples: usize) {
+        self.canvas.blend_samples(x, y, color, mask, samples);
+    }
+
+    fn fill_span(&mut self, x0: i32, x1: i32, y: i32, color: &Color) {
+        self.canvas.fill_span(x0, x1, y, color);
+    }
+
+    fn fill_gradient_span(&mut self, x0: i32, x1: i32, y: i32, from: &Color, to: &Color, t: (f64, f64)) {
+        self.canvas.fill_gradient_span(x0, x1, y, from, to, t);
+    }
+
+    fn blend_span(&mut self, x0: i32, x1: i32, y: i32, color: &Color, coverage: f64) {
+        self.canvas.blend_span(x0, x1, y, color, coverage);
+    }
+
+    fn draw_layer(&mut self, opacity: f64, draw: &mut dyn FnMut(&mut dyn Canvas)) {
+        let depth = &mut *self.depth;
+        self.canvas.draw_layer(opacity, &mut |layer| draw(&mut DepthCanvas::new(layer, &mut *depth)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use graphic::Framebuffer;
+
+    #[test]
+    fn closer_pixels_pass() {
+        let mut framebuffer = Framebuffer::new(2, 1);
+        let mut depth = DepthBuffer::new(2, 1);
+        {
+            let mut canvas = DepthCanvas::new(&mut framebuffer, &mut depth);
+            canvas.blend_pixel_depth(0, 0, &Color::red(), 1.0, 1.0);
+            canvas.blend_pixel_depth(0, 0, &Color::blue(), 1.0, 2.0);
+            canvas.blend_pixel_depth(1, 0, &Color::blue(), 1.0, 2.0);
+            canvas.blend_pixel_depth(1, 0, &Color::red(), 1.0, 1.0);
+        }
+        assert_eq!(255, framebuffer.get_pixel(0, 0).unwrap().r);
+        assert_eq!(255, framebuffer.get_pixel(1, 0).unwrap().r);
+        assert_eq!(Some(1.0), depth.get(1, 0));
+        assert!(!depth.test(2, 0, 0.0));
+    }
+}
@@ -0,0 +1,419 @@
+extern crate raster;
+
+use raster::Color;
+use graphic::{Canvas, BlendMode, blend_with, blend_with_linear};
+use graphic::layer::{self, LayerBuffer};
+use graphic::simd::{self, Isa};
+
+/// Byte order of a single pixel inside a Framebuffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// Red, Green, Blue, Alpha
+    Rgba8,
+    /// Blue, Green, Red, Alpha
+    Bgra8
+}
+
+/// Owned 8-bit pixel buffer with a configurable row stride and pixel layout
+#[derive(Debug, Clone)]
+pub struct Framebuffer {
+    width: i32,
+    height: i32,
+    stride: usize,
+    format: PixelFormat,
+    linear_blending: bool,
+    data: Vec<u8>
+}
+
+impl Framebuffer {
+    /// Returns a transparent black RGBA8 Framebuffer with tightly packed rows
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - Width in pixels
+    /// * `height` - Height in pixels
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// use graphic_library::graphic::Framebuffer;
+    /// fn main(){
+    /// let framebuffer = Framebuffer::new(640, 480);
+    /// }
+    /// ```
+    pub fn new(width: i32, height: i32) -> Framebuffer {
+        let width = width.max(0);
+        Framebuffer::with_format(width, height, width as usize * 4, PixelFormat::Rgba8).unwrap()
+    }
+
+    /// Returns a transparent black Framebuffer with a given row stride and pixel layout
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - Width in pixels
+    /// * `height` - Height in pixels
+    /// * `stride` - Bytes per row, at least `width * 4`
+    /// * `format` - Byte order of the pixels
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// use graphic_library::graphic::{Framebuffer, PixelFormat};
+    /// fn main(){
+    /// //Rows padded to 2816 bytes, as expected by some video encoders
+    /// let framebuffer = Framebuffer::with_format(700, 480, 2816, PixelFormat::Bgra8).unwrap();
+    /// }
+    /// ```
+    pub fn with_format(width: i32, height: i32, stride: usize, format: PixelFormat) -> Result<Framebuffer, String> {
+        let width = width.max(0);
+        let height = height.max(0);
+        if stride < width as usize * 4 {
+            return Err(format!("Stride {} is too small for a width of {} pixels.", stride, width));
+        }
+        Ok(Framebuffer {
+            width,
+            height,
+            stride,
+            format,
+            linear_blending: false,
+            data: vec![0; stride * height as usize]
+        })
+    }
+
+    /// Returns the number of bytes per row
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// Returns the byte order of the pixels
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+
+    /// Sets whether blending, coverage and color interpolation happen in linear light
+    ///
+    /// Pixels stay sRGB encoded, they are decoded for blending and encoded again on write.
+    pub fn set_linear_blending(&mut self, linear_blending: bool) {
+        self.linear_blending = linear_blending;
+    }
+
+    /// Overwrites every pixel with a color
+    pub fn clear(&mut self, color: &Color) {
+        for y in 0..self.height {
+            self.fill_span(0, self.width, y, color);
+        }
+    }
+
+    /// Returns the raw pixel data, `stride` bytes per row
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the raw pixel data mutably, `stride` bytes per row
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        &mut self.data
+    }
+
+    /// Consumes the Framebuffer and returns its raw pixel data, `stride` bytes per row
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Copies the pixels into a caller-provided buffer
+    ///
+    /// # Arguments
+    ///
+    /// * `dst` - Destination buffer, at least `dst_stride * height` bytes long
+    /// * `dst_stride` - Bytes per row of the destination, at least `width * 4`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// use graphic_library::graphic::Framebuffer;
+    /// fn main(){
+    /// let framebuffer = Framebuffer::new(640, 480);
+    /// let mut frame = vec![0u8; 640 * 480 * 4];
+    /// framebuffer.copy_to(&mut frame, 640 * 4).unwrap();
+    /// }
+    /// ```
+    pub fn copy_to(&self, dst: &mut [u8], dst_stride: usize) -> Result<(), String> {
+        let row = self.width as usize * 4;
+        if dst_stride < row {
+            return Err(format!("Stride {} is too small for a width of {} pixels.", dst_stride, self.width));
+        }
+        if self.height > 0 && dst.len() < dst_stride * (self.height as usize - 1) + row {
+            return Err(format!("Buffer of {} bytes is too small for {}x{} pixels.", dst.len(), self.width, self.height));
+        }
+        for y in 0..self.height as usize {
+            dst[y * dst_stride..y * dst_stride + row].copy_from_slice(&self.data[y * self.stride..y * self.stride + row]);
+        }
+        Ok(())
+    }
+
+    /// Blends another Framebuffer over this one with straight alpha, pixels without alpha on top are skipped
+    ///
+    /// Only the pixels both Framebuffers share from the top left corner are blended.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - Framebuffer on top
+    /// * `opacity` - Opacity of `src` between 0..1, multiplies the alpha of every pixel
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// extern crate raster;
+    /// use raster::Color;
+    /// use graphic_library::graphic::{Canvas, Framebuffer};
+    /// fn main(){
+    /// //Lays a red overlay at half opacity over a white image
+    /// let mut image = Framebuffer::new(4, 4);
+    /// image.clear(&Color::white());
+    /// let mut overlay = Framebuffer::new(4, 4);
+    /// overlay.clear(&Color::red());
+    /// image.composite(&overlay, 0.5);
+    /// assert_eq!(128, image.get_pixel(0, 0).unwrap().g);
+    /// }
+    /// ```
+    pub fn composite(&mut self, src: &Framebuffer, opacity: f64) {
+        self.composite_layer(src, 0, 0, opacity, BlendMode::Normal);
+    }
+
+    /// Returns a copy of the pixels as RGBA8 raster::Image
+    pub fn to_image(&self) -> raster::Image {
+        let mut image = raster::Image::blank(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let color = Canvas::get_pixel(self, x, y).unwrap();
+                Canvas::set_pixel(&mut image, x, y, &color);
+            }
+        }
+        image
+    }
+
+    fn offset(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            None
+        } else {
+            Some(y as usize * self.stride + x as usize * 4)
+        }
+    }
+
+    /// Returns the byte range of the span `x0..x1` of row `y` clipped to the Framebuffer, None if it is empty
+    fn span(&self, x0: i32, x1: i32, y: i32) -> Option<std::ops::Range<usize>> {
+        let x0 = x0.max(0);
+        let x1 = x1.min(self.width);
+        if y < 0 || y >= self.height || x0 >= x1 {
+            return None;
+        }
+        let start = self.offset(x0, y).unwrap();
+        Some(start..start + (x1 - x0) as usize * 4)
+    }
+
+    fn encode(&self, color: &Color) -> [u8; 4] {
+        match self.format {
+            PixelFormat::Rgba8 => [color.r, color.g, color.b, color.a],
+            PixelFormat::Bgra8 => [color.b, color.g, color.r, color.a]
+        }
+    }
+}
+
+impl Canvas for Framebuffer {
+    fn width(&self) -> i32 {
+        self.width
+    }
+
+    fn height(&self) -> i32 {
+        self.height
+    }
+
+    fn get_pixel(&self, x: i32, y: i32) -> Option<Color> {
+        self.offset(x, y).map(|i| {
+            let p = &self.data[i..i + 4];
+            match self.format {
+                PixelFormat::Rgba8 => Color::rgba(p[0], p[1], p[2], p[3]),
+                PixelFormat::Bgra8 => Color::rgba(p[2], p[1], p[0], p[3])
+            }
+        })
+    }
+
+    fn set_pixel(&mut self, x: i32, y: i32, color: &Color) {
+        if let Some(i) = self.offset(x, y) {
+            let pixel = self.encode(color);
+            self.data[i..i + 4].copy_from_slice(&pixel);
+        }
+    }
+
+    fn blend_pixel(&mut self, x: i32, y: i32, color: &Color, coverage: f64) {
+        self.blend_span(x, x + 1, y, color, coverage);
+    }
+
+    fn linear_blending(&self) -> bool {
+        self.linear_blending
+    }
+
+    fn fill_span(&mut self, x0: i32, x1: i32, y: i32, color: &Color) {
+        if let Some(span) = self.span(x0, x1, y) {
+            let pixel = self.encode(color);
+            simd::fill(Isa::detect(), &mut self.data[span], pixel);
+        }
+    }
+
+    fn fill_gradient_span(&mut self, x0: i32, x1: i32, y: i32, from: &Color, to: &Color, t: (f64, f64)) {
+        if let Some(span) = self.span(x0, x1, y) {
+            //Pixels clipped on the left shift the start of the gradient
+            let t = (t.0 + (x0.max(0) - x0) as f64 * t.1, t.1);
+            let (from, to) = (self.encode(from), self.encode(to));
+            simd::gradient(Isa::detect(), &mut self.data[span], &from, &to, t);
+        }
+    }
+
+    fn blend_span(&mut self, x0: i32, x1: i32, y: i32, color: &Color, coverage: f64) {
+        if coverage <= 0.0 {
+            return;
+        }
+        if coverage >= 1.0 && color.a == 255 {
+            self.fill_span(x0, x1, y, color);
+            return;
+        }
+        if let Some(span) = self.span(x0, x1, y) {
+            //Blends the stored bytes in place, the channel order does not matter as long as it is the same
+            let src = self.encode(color);
+            let linear_blending = self.linear_blending;
+            simd::blend(Isa::detect(), &mut self.data[span], &src, coverage, linear_blending);
+        }
+    }
+
+    fn draw_layer(&mut self, opacity: f64, draw: &mut dyn FnMut(&mut dyn Canvas)) {
+        layer::draw_layer(self, opacity, draw);
+    }
+}
+
+impl LayerBuffer for Framebuffer {
+    fn blank(&self, width: i32, height: i32) -> Framebuffer {
+        let width = width.max(0);
+        let mut framebuffer = Framebuffer::with_format(width, height, width as usize * 4, self.format).unwrap();
+        framebuffer.set_linear_blending(self.linear_blending);
+        framebuffer
+    }
+
+    fn composite_layer(&mut self, layer: &Framebuffer, x: i32, y: i32, opacity: f64, mode: BlendMode) {
+        let width = layer.width.min(self.width - x);
+        let height = layer.height.min(self.height - y);
+        if mode == BlendMode::Normal && layer.format == self.format {
+            let isa = Isa::detect();
+            //Columns of the layer left of the Framebuffer are skipped like the ones right of it
+            let skip = (-x).max(0) as usize * 4;
+            for row in 0..height.max(0) {
+                if let Some(span) = self.span(x, x + width, y + row) {
+                    let start = row as usize * layer.stride + skip;
+                    let src = &layer.data[start..start + span.len()];
+                    simd::composite(isa, &mut self.data[span], src, opacity, self.linear_blending);
+                }
+            }
+            return;
+        }
+        for ly in 0..height {
+            for lx in 0..width {
+                let color = Canvas::get_pixel(layer, lx, ly).unwrap();
+                if color.a == 0 {
+                    continue;
+                }
+                if mode == BlendMode::Normal {
+                    self.blend_pixel(x + lx, y + ly, &color, opacity);
+                } else if let Some(dst) = Canvas::get_pixel(self, x + lx, y + ly) {
+                    let blended = if self.linear_blending {
+                        blend_with_linear(&dst, &color, opacity, mode)
+                    } else {
+                        blend_with(&dst, &color, opacity, mode)
+                    };
+                    self.set_pixel(x + lx, y + ly, &blended);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_and_get_pixel() {
+        let mut framebuffer = Framebuffer::new(4, 3);
+        framebuffer.set_pixel(3, 2, &Color::rgba(1, 2, 3, 4));
+        let color = framebuffer.get_pixel(3, 2).unwrap();
+        assert_eq!((1, 2, 3, 4), (color.r, color.g, color.b, color.a));
+    }
+
+    #[test]
+    fn ignores_pixels_outside() {
+        let mut framebuffer = Framebuffer::new(4, 3);
+        framebuffer.set_pixel(4, 0, &Color::white());
+        framebuffer.set_pixel(-1, 0, &Color::white());
+        assert!(framebuffer.get_pixel(4, 0).is_none());
+        assert!(framebuffer.data.iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn blend_pixel() {
+        let mut framebuffer = Framebuffer::new(1, 1);
+        framebuffer.set_pixel(0, 0, &Color::rgb(0, 0, 255));
+        framebuffer.blend_pixel(0, 0, &Color::rgb(255, 0, 0), 0.25);
+        let color = framebuffer.get_pixel(0, 0).unwrap();
+        assert_eq!((64, 0, 191, 255), (color.r, color.g, color.b, color.a));
+    }
+
+    #[test]
+    fn bgra_layout_with_stride() {
+        let mut framebuffer = Framebuffer::with_format(2, 2, 12, PixelFormat::Bgra8).unwrap();
+        framebuffer.set_pixel(1, 1, &Color::rgba(1, 2, 3, 4));
+        assert_eq!(&[3, 2, 1, 4], &framebuffer.as_bytes()[16..20]);
+        assert_eq!(1, framebuffer.get_pixel(1, 1).unwrap().r);
+        assert!(Framebuffer::with_format(2, 2, 7, PixelFormat::Rgba8).is_err());
+    }
+
+    #[test]
+    fn copy_to_repacks_rows() {
+        let mut framebuffer = Framebuffer::with_format(1, 2, 8, PixelFormat::Rgba8).unwrap();
+        framebuffer.clear(&Color::rgba(9, 9, 9, 9));
+        let mut dst = [0u8; 8];
+        framebuffer.copy_to(&mut dst, 4).unwrap();
+        assert_eq!([9u8; 8], dst);
+        assert!(framebuffer.copy_to(&mut dst[..7], 4).is_err());
+    }
+
+    #[test]
+    fn blend_pixel_linear() {
+        let mut framebuffer = Framebuffer::new(1, 1);
+        framebuffer.set_linear_blending(true);
+        framebuffer.set_pixel(0, 0, &Color::rgb(0, 0, 255));
+        framebuffer.blend_pixel(0, 0, &Color::rgb(255, 0, 0), 0.25);
+        let color = framebuffer.get_pixel(0, 0).unwrap();
+        assert_eq!((137, 0, 225, 255), (color.r, color.g, color.b, color.a));
+    }
+
+    #[test]
+    fn composite_layer_left_of_the_framebuffer() {
+        let mut framebuffer = Framebuffer::new(4, 2);
+        let mut layer = framebuffer.blank(3, 3);
+        layer.set_pixel(1, 1, &Color::red());
+        layer.set_pixel(2, 1, &Color::blue());
+        framebuffer.composite_layer(&layer, -1, -1, 1.0, BlendMode::Normal);
+        assert_eq!((255, 0), (framebuffer.get_pixel(0, 0).unwrap().r, framebuffer.get_pixel(0, 0).unwrap().b));
+        assert_eq!(255, framebuffer.get_pixel(1, 0).unwrap().b);
+        assert_eq!(0, framebuffer.get_pixel(2, 0).unwrap().a);
+    }
+
+    #[test]
+    fn fill_span_is_clipped() {
+        let mut framebuffer = Framebuffer::new(4, 1);
+        framebuffer.fill_span(-2, 2, 0, &Color::white());
+        assert_eq!(255, framebuffer.get_pixel(1, 0).unwrap().r);
+        assert_eq!(0, framebuffer.get_pixel(2, 0).unwrap().r);
+    }
+}
@@ -0,0 +1,247 @@
+extern crate raster;
+
+use raster::Color;
+use graphic::{Canvas, BlendMode, Framebuffer, ToneMapping, srgb_to_linear, linear_to_srgb};
+use graphic::layer::{self, LayerBuffer};
+
+/// Owned RGBA pixel buffer storing linear light with one `f32` per channel
+///
+/// Colors are decoded from sRGB when written and values may exceed 1.0,
+/// use `tone_map` to turn the buffer into a displayable Framebuffer.
+#[derive(Debug, Clone)]
+pub struct HdrBuffer {
+    width: i32,
+    height: i32,
+    blend_mode: BlendMode,
+    data: Vec<f32>
+}
+
+impl HdrBuffer {
+    /// Returns a transparent black HdrBuffer
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - Width in pixels
+    /// * `height` - Height in pixels
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// use graphic_library::graphic::HdrBuffer;
+    /// fn main(){
+    /// let buffer = HdrBuffer::new(640, 480);
+    /// }
+    /// ```
+    pub fn new(width: i32, height: i32) -> HdrBuffer {
+        let width = width.max(0);
+        let height = height.max(0);
+        HdrBuffer {
+            width,
+            height,
+            blend_mode: BlendMode::Normal,
+            data: vec![0.0; (width * height * 4) as usize]
+        }
+    }
+
+    /// Returns how drawn colors are combined with the buffer
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    /// Sets how drawn colors are combined with the buffer
+    ///
+    /// With a blend mode other than `BlendMode::Normal` every write, including `set_pixel`, blends.
+    /// `BlendMode::Additive` accumulates light, so overlapping shapes can exceed 1.0.
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.blend_mode = blend_mode;
+    }
+
+    /// Returns the linear RGBA value of a pixel or `None` if it lies outside of the buffer
+    pub fn get(&self, x: i32, y: i32) -> Option<[f32; 4]> {
+        self.offset(x, y).map(|i| [self.data[i], self.data[i + 1], self.data[i + 2], self.data[i + 3]])
+    }
+
+    /// Overwrites the linear RGBA value of a pixel, pixels outside of the buffer are ignored
+    pub fn set(&mut self, x: i32, y: i32, value: [f32; 4]) {
+        if let Some(i) = self.offset(x, y) {
+            self.data[i..i + 4].copy_from_slice(&value);
+        }
+    }
+
+    /// Overwrites every pixel with a linear RGBA value
+    pub fn clear(&mut self, value: [f32; 4]) {
+        for pixel in self.data.chunks_mut(4) {
+            pixel.copy_from_slice(&value);
+        }
+    }
+
+    /// Overwrites the pixels at `x, y` with the pixels of a rectangle of another buffer
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - X-Coordinate the rectangle is copied to
+    /// * `y` - Y-Coordinate the rectangle is copied to
+    /// * `src` - Buffer to copy from
+    /// * `rect` - Rectangle of `src` as `(x0, y0, x1, y1)`, it has to fit into both buffers
+    ///
+    #[cfg(feature = "parallel")]
+    pub(crate) fn copy_rect(&mut self, x: i32, y: i32, src: &HdrBuffer, rect: (i32, i32, i32, i32)) {
+        let (x0, y0, x1, y1) = rect;
+        let len = (x1 - x0).max(0) as usize * 4;
+        for row in 0..y1 - y0 {
+            let start = ((y + row) * self.width + x) as usize * 4;
+            let src_start = ((y0 + row) * src.width + x0) as usize * 4;
+            self.data[start..start + len].copy_from_slice(&src.data[src_start..src_start + len]);
+        }
+    }
+
+    /// Returns the buffer converted to 8-bit sRGB
+    ///
+    /// # Arguments
+    ///
+    /// * `tone_mapping` - Operator compressing values above 1.0
+    /// * `exposure` - Exposure in stops, every stop doubles the brightness
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// use graphic_library::graphic::{HdrBuffer, ToneMapping};
+    /// fn main(){
+    /// let buffer = HdrBuffer::new(640, 480);
+    /// let framebuffer = buffer.tone_map(ToneMapping::AcesFilmic, -1.0);
+    /// }
+    /// ```
+    pub fn tone_map(&self, tone_mapping: ToneMapping, exposure: f32) -> Framebuffer {
+        let mut framebuffer = Framebuffer::new(self.width, self.height);
+        self.tone_map_into(&mut framebuffer, tone_mapping, exposure);
+        framebuffer
+    }
+
+    /// Converts the buffer to 8-bit sRGB into an existing Canvas of at least the same size
+    ///
+    /// # Arguments
+    ///
+    /// * `canvas` - Destination of the converted pixels
+    /// * `tone_mapping` - Operator compressing values above 1.0
+    /// * `exposure` - Exposure in stops, every stop doubles the brightness
+    ///
+    pub fn tone_map_into(&self, canvas: &mut dyn Canvas, tone_mapping: ToneMapping, exposure: f32) {
+        let scale = 2f32.powf(exposure);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let p = self.get(x, y).unwrap();
+                let color = Color::rgba(linear_to_srgb(tone_mapping.map(p[0] * scale)),
+                                        linear_to_srgb(tone_mapping.map(p[1] * scale)),
+                                        linear_to_srgb(tone_mapping.map(p[2] * scale)),
+                                        (p[3].clamp(0.0, 1.0) * 255.0).round() as u8);
+                canvas.set_pixel(x, y, &color);
+            }
+        }
+    }
+
+    fn offset(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            None
+        } else {
+            Some(((y * self.width + x) * 4) as usize)
+        }
+    }
+}
+
+fn decode(color: &Color) -> [f32; 4] {
+    [srgb_to_linear(color.r), srgb_to_linear(color.g), srgb_to_linear(color.b), color.a as f32 / 255.0]
+}
+
+impl Canvas for HdrBuffer {
+    fn width(&self) -> i32 {
+        self.width
+    }
+
+    fn height(&self) -> i32 {
+        self.height
+    }
+
+    fn get_pixel(&self, x: i32, y: i32) -> Option<Color> {
+        self.get(x, y).map(|p| Color::rgba(linear_to_srgb(p[0]), linear_to_srgb(p[1]), linear_to_srgb(p[2]), (p[3].clamp(0.0, 1.0) * 255.0).round() as u8))
+    }
+
+    fn set_pixel(&mut self, x: i32, y: i32, color: &Color) {
+        match self.blend_mode {
+            BlendMode::Normal => self.set(x, y, decode(color)),
+            _ => self.blend_pixel(x, y, color, 1.0)
+        }
+    }
+
+    fn linear_blending(&self) -> bool {
+        true
+    }
+
+    fn blend_pixel(&mut self, x: i32, y: i32, color: &Color, coverage: f64) {
+        if let Some(dst) = self.get(x, y) {
+            self.set(x, y, self.blend_mode.blend(dst, decode(color), coverage.clamp(0.0, 1.0) as f32));
+        }
+    }
+
+    fn draw_layer(&mut self, opacity: f64, draw: &mut dyn FnMut(&mut dyn Canvas)) {
+        layer::draw_layer(self, opacity, draw);
+    }
+}
+
+impl LayerBuffer for HdrBuffer {
+    fn blank(&self, width: i32, height: i32) -> HdrBuffer {
+        let mut hdr = HdrBuffer::new(width, height);
+        hdr.set_blend_mode(self.blend_mode);
+        hdr
+    }
+
+    /// Blends in linear light without clamping, layers with `BlendMode::Normal` use the blend mode of the buffer
+    fn composite_layer(&mut self, layer: &HdrBuffer, x: i32, y: i32, opacity: f64, mode: BlendMode) {
+        let mode = if mode == BlendMode::Normal { self.blend_mode } else { mode };
+        let opacity = opacity.clamp(0.0, 1.0) as f32;
+        for ly in (-y).max(0)..layer.height.min(self.height - y) {
+            for lx in (-x).max(0)..layer.width.min(self.width - x) {
+                let src = layer.get(lx, ly).unwrap();
+                if src[3] <= 0.0 {
+                    continue;
+                }
+                let dst = self.get(x + lx, y + ly).unwrap();
+                self.set(x + lx, y + ly, mode.blend(dst, src, opacity));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn additive_blending_exceeds_one() {
+        let mut buffer = HdrBuffer::new(1, 1);
+        buffer.set_blend_mode(BlendMode::Additive);
+        for _ in 0..4 {
+            buffer.set_pixel(0, 0, &Color::rgb(255, 0, 0));
+        }
+        assert_eq!([4.0, 0.0, 0.0, 1.0], buffer.get(0, 0).unwrap());
+        assert_eq!(255, buffer.get_pixel(0, 0).unwrap().r);
+    }
+
+    #[test]
+    fn composite_layer_left_of_the_buffer() {
+        let mut buffer = HdrBuffer::new(2, 2);
+        let mut layer = buffer.blank(2, 2);
+        layer.set(1, 1, [2.0, 0.0, 0.0, 1.0]);
+        buffer.composite_layer(&layer, -1, -1, 1.0, BlendMode::Normal);
+        assert_eq!([2.0, 0.0, 0.0, 1.0], buffer.get(0, 0).unwrap());
+    }
+
+    #[test]
+    fn tone_map_with_exposure() {
+        let mut buffer = HdrBuffer::new(1, 1);
+        buffer.set(0, 0, [2.0, 1.0, 0.0, 1.0]);
+        let color = buffer.tone_map(ToneMapping::Reinhard, -1.0).get_pixel(0, 0).unwrap();
+        assert_eq!((188, 156, 0, 255), (color.r, color.g, color.b, color.a));
+    }
+}
@@ -0,0 +1,117 @@
+extern crate raster;
+
+use raster::Color;
+use graphic::{Canvas, BlendMode};
+
+/// Pixel buffer translucent layers are drawn into offscreen and composited from without losing precision
+pub(crate) trait LayerBuffer: Canvas + Sized {
+    /// Returns a transparent buffer of another size, storing and blending colors like `self`
+    fn blank(&self, width: i32, height: i32) -> Self;
+
+    /// Blends a layer over the pixels starting at `x, y` in the space colors are blended in
+    ///
+    /// # Arguments
+    ///
+    /// * `layer` - Buffer on top, pixels without alpha are skipped
+    /// * `x` - X-Coordinate the top left corner of the layer ends up at, parts outside of `self` are skipped
+    /// * `y` - Y-Coordinate the top left corner of the layer ends up at, parts outside of `self` are skipped
+    /// * `opacity` - Opacity of the layer between 0..1, multiplies the alpha of every pixel
+    /// * `mode` - Operation combining the layer with the pixels underneath
+    ///
+    fn composite_layer(&mut self, layer: &Self, x: i32, y: i32, opacity: f64, mode: BlendMode);
+}
+
+/// Returns the pixels `x0, y0, x1, y1` of a Canvas that can be written, `x1` and `y1` excluded
+pub(crate) fn writable_rect<C: Canvas + ?Sized>(canvas: &C) -> (i32, i32, i32, i32) {
+    let (x0, y0, x1, y1) = canvas.clip_rect();
+    (x0.max(0), y0.max(0), x1.min(canvas.width()), y1.min(canvas.height()))
+}
+
+/// Draws into a transparent layer of the kind of `canvas` covering its writable pixels and composites it
+pub(crate) fn draw_layer<B: LayerBuffer>(canvas: &mut B, opacity: f64, draw: &mut dyn FnMut(&mut dyn Canvas)) {
+    let (x0, y0, x1, y1) = writable_rect(canvas);
+    if x0 >= x1 || y0 >= y1 {
+        return;
+    }
+    let layer = canvas.blank(x1 - x0, y1 - y0);
+    let layer = draw_offscreen(canvas, layer, x0, y0, draw);
+    canvas.composite_layer(&layer, x0, y0, opacity, BlendMode::Normal);
+}
+
+/// Draws into a buffer placed at `x, y` of a Canvas and returns the buffer
+///
+/// The drawing sees the size and depth buffer of `canvas`, its pixels only end up in `buffer`.
+pub(crate) fn draw_offscreen<B: LayerBuffer, C: Canvas + ?Sized>(canvas: &mut C, buffer: B, x: i32, y: i32,
+                                                                 draw: &mut dyn FnMut(&mut dyn Canvas)) -> B {
+    let mut offscreen = Offscreen { buffer, canvas, x0: x, y0: y };
+    draw(&mut offscreen);
+    offscreen.buffer
+}
+
+/// Layer covering part of a larger Canvas, pixels outside of the layer are ignored
+///
+/// Depth tests are passed on to the Canvas underneath, so the layer stays depth tested against the scene.
+struct Offscreen<'a, B, C: Canvas + ?Sized + 'a> {
+    buffer: B,
+    canvas: &'a mut C,
+    x0: i32,
+    y0: i32
+}
+
+impl<'a, B: LayerBuffer, C: Canvas + ?Sized> Canvas for Offscreen<'a, B, C> {
+    fn width(&self) -> i32 {
+        self.canvas.width()
+    }
+
+    fn height(&self) -> i32 {
+        self.canvas.height()
+    }
+
+    fn get_pixel(&self, x: i32, y: i32) -> Option<Color> {
+        self.buffer.get_pixel(x - self.x0, y - self.y0)
+    }
+
+    fn set_pixel(&mut self, x: i32, y: i32, color: &Color) {
+        self.buffer.set_pixel(x - self.x0, y - self.y0, color);
+    }
+
+    fn blend_pixel(&mut self, x: i32, y: i32, color: &Color, coverage: f64) {
+        self.buffer.blend_pixel(x - self.x0, y - self.y0, color, coverage);
+    }
+
+    fn linear_blending(&self) -> bool {
+        self.buffer.linear_blending()
+    }
+
+    fn clip_rect(&self) -> (i32, i32, i32, i32) {
+        (self.x0, self.y0, self.x0 + self.buffer.width(), self.y0 + self.buffer.height())
+    }
+
+    fn depth_test(&mut self, x: i32, y: i32, depth: f64) -> bool {
+        self.canvas.depth_test(x, y, depth)
+    }
+
+    fn blend_samples(&mut self, x: i32, y: i32, color: &Color, mask: u32, samples: usize) {
+        self.buffer.blend_samples(x - self.x0, y - self.y0, color, mask, samples);
+    }
+
+    fn fill_span(&mut self, x0: i32, x1: i32, y: i32, color: &Color) {
+        self.buffer.fill_span(x0 - self.x0, x1 - self.x0, y - self.y0, color);
+    }
+
+    fn fill_gradient_span(&mut self, x0: i32, x1: i32, y: i32, from: &Color, to: &Color, t: (f64, f64)) {
+        self.buffer.fill_gradient_span(x0 - self.x0, x1 - self.x0, y - self.y0, from, to, t);
+    }
+
+    fn blend_span(&mut self, x0: i32, x1: i32, y: i32, color: &Color, coverage: f64) {
+        self.buffer.blend_span(x0 - self.x0, x1 - self.x0, y - self.y0, color, coverage);
+    }
+
+    fn draw_layer(&mut self, opacity: f64, draw: &mut dyn FnMut(&mut dyn Canvas)) {
+        let layer = self.buffer.blank(self.buffer.width(), self.buffer.height());
+        let (x0, y0) = (self.x0, self.y0);
+        //Nests through a trait object so the Offscreen type does not grow with every nested layer
+        let layer = draw_offscreen(self as &mut dyn Canvas, layer, x0, y0, draw);
+        self.buffer.composite_layer(&layer, 0, 0, opacity, BlendMode::Normal);
+    }
+}
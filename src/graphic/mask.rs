@@ -0,0 +1,101 @@
+extern crate raster;
+
+use raster::Color;
+use graphic::Canvas;
+
+/// Coverage-only render target, stores how much of every pixel has been drawn
+///
+/// Colors written into a Mask only contribute their alpha channel.
+#[derive(Debug, Clone)]
+pub struct Mask {
+    width: i32,
+    height: i32,
+    data: Vec<u8>
+}
+
+impl Mask {
+    /// Returns an empty Mask
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - Width in pixels
+    /// * `height` - Height in pixels
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// use graphic_library::graphic::Mask;
+    /// fn main(){
+    /// let mask = Mask::new(640, 480);
+    /// }
+    /// ```
+    pub fn new(width: i32, height: i32) -> Mask {
+        let width = width.max(0);
+        let height = height.max(0);
+        Mask {
+            width,
+            height,
+            data: vec![0; (width * height) as usize]
+        }
+    }
+
+    /// Returns the coverage of a pixel between 0..255, pixels outside of the Mask are uncovered
+    pub fn coverage(&self, x: i32, y: i32) -> u8 {
+        self.offset(x, y).map_or(0, |i| self.data[i])
+    }
+
+    /// Returns the coverage of all pixels row by row
+    pub fn as_slice(&self) -> &[u8] {
+        &self.data
+    }
+
+    fn offset(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            None
+        } else {
+            Some((y * self.width + x) as usize)
+        }
+    }
+}
+
+impl Canvas for Mask {
+    fn width(&self) -> i32 {
+        self.width
+    }
+
+    fn height(&self) -> i32 {
+        self.height
+    }
+
+    fn get_pixel(&self, x: i32, y: i32) -> Option<Color> {
+        self.offset(x, y).map(|i| Color::rgba(255, 255, 255, self.data[i]))
+    }
+
+    fn set_pixel(&mut self, x: i32, y: i32, color: &Color) {
+        if let Some(i) = self.offset(x, y) {
+            self.data[i] = color.a;
+        }
+    }
+
+    fn blend_pixel(&mut self, x: i32, y: i32, color: &Color, coverage: f64) {
+        if let Some(i) = self.offset(x, y) {
+            let sa = (color.a as f64 / 255.0) * coverage.clamp(0.0, 1.0);
+            let da = self.data[i] as f64 / 255.0;
+            self.data[i] = ((sa + da * (1.0 - sa)) * 255.0).round() as u8;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accumulates_coverage() {
+        let mut mask = Mask::new(1, 1);
+        mask.blend_pixel(0, 0, &Color::black(), 0.5);
+        mask.blend_pixel(0, 0, &Color::black(), 0.5);
+        assert_eq!(192, mask.coverage(0, 0));
+    }
+}
@@ -1,5 +1,36 @@
 //pub use self::two_dimensional_transformation as transformation;
-pub use self::renderer::Renderer;
+pub use self::renderer::{Renderer, ShapeId, LayerId};
+pub use self::antialiasing::{Antialiasing, DownsampleFilter, SamplePattern};
+pub use self::background::Background;
+pub use self::camera::Camera;
+pub use self::canvas::{Canvas, BlendMode, blend, blend_linear, blend_with, blend_with_linear};
+pub use self::coverage::CoverageRasterizer;
+pub use self::color::{ColorSpace, srgb_to_linear, linear_to_srgb, interpolate_color, interpolate_color_barycentric};
+pub use self::depth_buffer::{DepthBuffer, DepthCanvas};
+pub use self::framebuffer::{Framebuffer, PixelFormat};
+pub use self::hdr_buffer::HdrBuffer;
+pub use self::origin::Origin;
+pub use self::mask::Mask;
+pub use self::tone_mapping::ToneMapping;
 //pub mod two_dimensional_transformation;
 //pub mod matrix;
-pub mod renderer;
\ No newline at end of file
+pub mod renderer;
+mod antialiasing;
+mod background;
+mod camera;
+mod canvas;
+mod color;
+mod coverage;
+mod depth_buffer;
+mod framebuffer;
+mod hdr_buffer;
+mod layer;
+mod mask;
+mod origin;
+//Public for the benchmarks only
+#[doc(hidden)]
+pub mod simd;
+mod spatial_index;
+#[cfg(feature = "parallel")]
+mod tiles;
+mod tone_mapping;
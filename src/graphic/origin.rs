@@ -0,0 +1,40 @@
+use geometric::Transform2D;
+
+/// Corner of the image world coordinates start from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Origin {
+    /// (0,0) is the top left pixel and y grows downwards, like in image files
+    #[default]
+    TopLeft,
+    /// (0,0) is the bottom left corner and y grows upwards, like in plots and maps
+    BottomLeft
+}
+
+impl Origin {
+    /// Returns the Transformation from coordinates with this Origin to pixel rows of an image
+    ///
+    /// Flips the area of the image, the bottom edge of the image at y=0 becomes y=`height`.
+    /// Points address whole pixels and keep the pixel whose corner they lie on, see `Geometric2D::apply_view`.
+    ///
+    /// # Arguments
+    ///
+    /// * `height` - Height of the image in pixels
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// use graphic_library::graphic::Origin;
+    /// fn main(){
+    /// //The bottom edge and the center of the bottom row of an image with 100 rows
+    /// assert_eq!((5.0, 100.0), Origin::BottomLeft.to_screen(100).apply(5.0, 0.0));
+    /// assert_eq!((5.5, 99.5), Origin::BottomLeft.to_screen(100).apply(5.5, 0.5));
+    /// }
+    /// ```
+    pub fn to_screen(self, height: i32) -> Transform2D {
+        match self {
+            Origin::TopLeft => Transform2D::identity(),
+            Origin::BottomLeft => Transform2D::scaling(1.0, -1.0).then(&Transform2D::translation(0.0, height as f64))
+        }
+    }
+}
@@ -1,41 +1,1320 @@
 extern crate raster;
 
-use std::collections::LinkedList;
-use std::cell::RefCell;
-use std::rc::Rc;
-use geometric::Geometric2D;
+use std::cell::{Ref, RefCell};
+use std::collections::{HashMap, HashSet};
+use raster::Color;
+use geometric::{BoundingBox, Geometric2D, Point2D, Transform2D};
+use graphic::spatial_index::SpatialIndex;
+#[cfg(feature = "parallel")]
+use graphic::tiles;
+use graphic::antialiasing::{self, MultisampleBuffer};
+use graphic::layer::{self, LayerBuffer};
+use graphic::{Antialiasing, Background, Camera, Canvas, Origin, BlendMode, DepthBuffer, DepthCanvas, Framebuffer, HdrBuffer, ToneMapping};
 
-pub struct Renderer<'a> {
-    vertices: LinkedList<&'a Rc<RefCell<Box<Geometric2D>>>>,
-    image: raster::Image
+/// Stable handle of a Geometric-Object owned by a Renderer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ShapeId(pub(crate) u64);
+
+/// Stable handle of a layer of a Renderer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LayerId(u64);
+
+/// Geometric-Object of a scene together with its layer
+struct Entry {
+    geo: Box<dyn Geometric2D>,
+    layer: LayerId,
+    z_index: i32,
+    /// Position among the shapes with the same z-index, the largest one ends up on top
+    order: i64
+}
+
+/// Pixel buffer a layer is drawn into
+enum Target<'a> {
+    Ldr(&'a mut Framebuffer),
+    Hdr(&'a mut HdrBuffer),
+    Samples(&'a mut MultisampleBuffer)
+}
+
+impl<'a> Target<'a> {
+    fn canvas(&mut self) -> &mut dyn Canvas {
+        match *self {
+            Target::Ldr(ref mut framebuffer) => &mut **framebuffer,
+            Target::Hdr(ref mut hdr) => &mut **hdr,
+            Target::Samples(ref mut samples) => &mut **samples
+        }
+    }
+}
+
+/// Named set of Geometric-Objects composited as a whole
+struct Layer {
+    id: LayerId,
+    name: String,
+    opacity: f64,
+    visible: bool,
+    blend_mode: BlendMode
+}
+
+impl Layer {
+    /// Returns whether drawing straight into the target gives the same result as compositing
+    fn is_direct(&self) -> bool {
+        self.opacity >= 1.0 && self.blend_mode == BlendMode::Normal
+    }
+}
+
+/// Owns a scene of Geometric-Objects and draws them back to front
+///
+/// Shapes are grouped into layers, composited bottom to top. Within a layer shapes are drawn
+/// by ascending z-index, shapes with the same z-index in insertion order.
+/// With depth testing enabled, the depth of the Points decides instead which pixel of a layer ends up on top.
+pub struct Renderer {
+    shapes: HashMap<ShapeId, Entry>,
+    next_id: u64,
+    /// Order of the next shape brought to the front
+    front: i64,
+    /// Order of the shape sent to the back last
+    back: i64,
+    /// Layers, the last one ends up on top
+    layers: Vec<Layer>,
+    next_layer: u64,
+    framebuffer: Framebuffer,
+    hdr: Option<HdrBuffer>,
+    depth: Option<DepthBuffer>,
+    /// Hierarchy over the bounds of the shapes
+    index: RefCell<SpatialIndex>,
+    /// Shapes handed out mutably, their bounds are refitted into the index on the next query
+    changed: RefCell<HashSet<ShapeId>>,
+    background: Background,
+    camera: Option<Camera>,
+    origin: Origin,
+    antialiasing: Antialiasing,
+    #[cfg(feature = "parallel")]
+    parallel: bool,
+    tone_mapping: ToneMapping,
+    exposure: f32
 }
 
-impl<'a> Renderer<'a> {
+impl Renderer {
     //Construct a new Renderer
-    pub fn new(height: i32, width: i32) -> Renderer<'a> {
+    pub fn new(width: i32, height: i32) -> Renderer {
+        let mut framebuffer = Framebuffer::new(width, height);
+        framebuffer.clear(&Color::black());
+        Renderer::with_framebuffer(framebuffer)
+    }
+
+    /// Returns a Renderer drawing into the given Framebuffer, e.g. one with a custom stride or pixel layout
+    pub fn with_framebuffer(framebuffer: Framebuffer) -> Renderer {
         Renderer {
-            vertices: LinkedList::new(),
-            image: raster::Image::blank(height, width)
+            shapes: HashMap::new(),
+            next_id: 0,
+            front: 0,
+            back: 0,
+            layers: vec![Layer {
+                id: LayerId(0),
+                name: "default".to_owned(),
+                opacity: 1.0,
+                visible: true,
+                blend_mode: BlendMode::Normal
+            }],
+            next_layer: 1,
+            framebuffer,
+            hdr: None,
+            depth: None,
+            index: RefCell::new(SpatialIndex::new(Vec::new())),
+            changed: RefCell::new(HashSet::new()),
+            background: Background::default(),
+            camera: None,
+            origin: Origin::TopLeft,
+            antialiasing: Antialiasing::Off,
+            #[cfg(feature = "parallel")]
+            parallel: false,
+            tone_mapping: ToneMapping::Clamp,
+            exposure: 0.0
+        }
+    }
+
+    /// Returns a Renderer accumulating in a linear light HdrBuffer
+    ///
+    /// Every draw tone maps the HdrBuffer into the Framebuffer, which is what `save_as` writes.
+    pub fn new_hdr(width: i32, height: i32) -> Renderer {
+        let mut renderer = Renderer::new(width, height);
+        let mut hdr = HdrBuffer::new(width, height);
+        hdr.clear([0.0, 0.0, 0.0, 1.0]);
+        renderer.hdr = Some(hdr);
+        renderer
+    }
+
+    /// Sets the operator and exposure in stops used to convert the HdrBuffer to 8-bit
+    pub fn set_tone_mapping(&mut self, tone_mapping: ToneMapping, exposure: f32) {
+        self.tone_mapping = tone_mapping;
+        self.exposure = exposure;
+    }
+
+    /// Sets whether blending, anti-aliasing coverage and color interpolation happen in linear light
+    ///
+    /// The HdrBuffer of `new_hdr` always works in linear light.
+    pub fn set_linear_blending(&mut self, linear_blending: bool) {
+        self.framebuffer.set_linear_blending(linear_blending);
+    }
+
+    /// Sets what `clear` and `render_frame` paint before drawing, opaque black by default
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// extern crate raster;
+    /// use raster::Color;
+    /// use graphic_library::graphic::{Background, Renderer};
+    /// fn main(){
+    /// let mut renderer = Renderer::new(10, 10);
+    /// renderer.set_background(Background::Color(Color::white()));
+    /// renderer.render_frame();
+    /// }
+    /// ```
+    pub fn set_background(&mut self, background: Background) {
+        self.background = background;
+    }
+
+    /// Returns what `clear` and `render_frame` paint before drawing
+    pub fn background(&self) -> &Background {
+        &self.background
+    }
+
+    /// Overwrites all pixels with the Background, removing everything drawn before
+    pub fn clear(&mut self) {
+        self.background.paint(&mut self.framebuffer);
+        if let Some(ref mut hdr) = self.hdr {
+            let blend_mode = hdr.blend_mode();
+            hdr.set_blend_mode(BlendMode::Normal);
+            self.background.paint(hdr);
+            hdr.set_blend_mode(blend_mode);
         }
     }
+
+    /// Clears to the Background and draws all Geometric-Objects, e.g. for each frame of an animation
+    pub fn render_frame(&mut self) {
+        self.clear();
+        self.draw();
+    }
+
+    /// Sets the Camera mapping world coordinates onto the pixels, `None` uses world coordinates as pixels
+    ///
+    /// The shapes themselves stay untouched, so the same scene can be drawn from several Cameras.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// use graphic_library::graphic::{Camera, Renderer};
+    /// use graphic_library::geometric::{Circle2D, Point2D};
+    /// fn main(){
+    /// let mut renderer = Renderer::new(100, 100);
+    /// renderer.add(Circle2D::new(10.0, Point2D::new(1000.0, 1000.0)));
+    /// //Shows the circle twice as large in the middle of the image
+    /// renderer.set_camera(Some(Camera::new(1000.0, 1000.0, 2.0, 0.0)));
+    /// renderer.render_frame();
+    /// }
+    /// ```
+    pub fn set_camera(&mut self, camera: Option<Camera>) {
+        self.camera = camera;
+    }
+
+    /// Returns the Camera mapping world coordinates onto the pixels
+    pub fn camera(&self) -> Option<Camera> {
+        self.camera
+    }
+
+    /// Sets the corner world coordinates start from, applied to every shape after the Camera
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// use graphic_library::graphic::{Origin, Renderer};
+    /// use graphic_library::geometric::Point2D;
+    /// fn main(){
+    /// let mut renderer = Renderer::new(100, 100);
+    /// renderer.set_origin(Origin::BottomLeft);
+    /// //Ends up in the bottom left pixel
+    /// renderer.add(Point2D::new(0.0, 0.0));
+    /// renderer.render_frame();
+    /// }
+    /// ```
+    pub fn set_origin(&mut self, origin: Origin) {
+        self.origin = origin;
+    }
+
+    /// Returns the corner world coordinates start from
+    pub fn origin(&self) -> Origin {
+        self.origin
+    }
+
+    /// Returns the Transformation from world coordinates to pixels
+    pub fn world_to_screen(&self) -> Transform2D {
+        self.view(self.framebuffer.width(), self.framebuffer.height())
+    }
+
+    /// Returns the Transformation from world coordinates to the pixels of an image of a given size
+    fn view(&self, width: i32, height: i32) -> Transform2D {
+        let camera = match self.camera {
+            Some(camera) => camera.to_screen(width, height),
+            None => Transform2D::identity()
+        };
+        camera.then(&self.origin.to_screen(height))
+    }
+
+    /// Returns the Transformation from pixels to world coordinates, e.g. to pick the shape under the mouse
+    pub fn screen_to_world(&self) -> Option<Transform2D> {
+        self.world_to_screen().inverse()
+    }
+
+    /// Sets the anti-aliasing applied on top of what each Geometric-Object draws, `Antialiasing::Off` by default
+    ///
+    /// Supersampled draws test depth per sample and leave the DepthBuffer of the Renderer untouched.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// use graphic_library::graphic::{Antialiasing, DownsampleFilter, Renderer, SamplePattern};
+    /// use graphic_library::geometric::{Point2D, Triangle2D};
+    /// fn main(){
+    /// let mut renderer = Renderer::new(100, 100);
+    /// renderer.add(Triangle2D::new(Point2D::new(10.0, 10.0), Point2D::new(90.0, 20.0), Point2D::new(40.0, 80.0)));
+    /// //Reference quality: 4x4 samples per pixel filtered with a Lanczos window
+    /// renderer.set_antialiasing(Antialiasing::Supersampling { factor: 4, filter: DownsampleFilter::Lanczos });
+    /// renderer.render_frame();
+    /// //Cheaper: coverage of four samples per pixel
+    /// renderer.set_antialiasing(Antialiasing::Multisampling(SamplePattern::rotated_grid()));
+    /// renderer.render_frame();
+    /// }
+    /// ```
+    pub fn set_antialiasing(&mut self, antialiasing: Antialiasing) {
+        self.antialiasing = antialiasing;
+    }
+
+    /// Returns the anti-aliasing applied on top of what each Geometric-Object draws
+    pub fn antialiasing(&self) -> &Antialiasing {
+        &self.antialiasing
+    }
+
+    /// Sets whether every draw tests the depth of each pixel against the pixels drawn before
+    ///
+    /// Smaller depths are closer to the viewer, the DepthBuffer is cleared at the start of every draw.
+    pub fn set_depth_test(&mut self, depth_test: bool) {
+        self.depth = if depth_test {
+            Some(DepthBuffer::new(self.framebuffer.width(), self.framebuffer.height()))
+        } else {
+            None
+        };
+    }
+
+    /// Returns the DepthBuffer of the last draw if depth testing is enabled
+    pub fn depth_buffer(&self) -> Option<&DepthBuffer> {
+        self.depth.as_ref()
+    }
+
+    /// Returns the HdrBuffer if the Renderer was created with `new_hdr`
+    pub fn hdr_buffer(&self) -> Option<&HdrBuffer> {
+        self.hdr.as_ref()
+    }
+
+    /// Returns the HdrBuffer mutably if the Renderer was created with `new_hdr`
+    pub fn hdr_buffer_mut(&mut self) -> Option<&mut HdrBuffer> {
+        self.hdr.as_mut()
+    }
+
+    /// Returns the rendered pixels
+    pub fn framebuffer(&self) -> &Framebuffer {
+        &self.framebuffer
+    }
+
+    /// Returns the rendered pixels mutably
+    pub fn framebuffer_mut(&mut self) -> &mut Framebuffer {
+        &mut self.framebuffer
+    }
+
+    /// Consumes the Renderer and returns the rendered pixels
+    pub fn into_framebuffer(self) -> Framebuffer {
+        self.framebuffer
+    }
+
     pub fn save(self) {
         self.save_as("test_tmp.png".to_owned());
     }
 
-    pub fn add(&mut self, geo: &'a Rc<RefCell<Box<Geometric2D>>>) {
-        self.vertices.push_front(geo);
+    /// Returns the layer every Geometric-Object starts in, named "default"
+    pub fn default_layer(&self) -> LayerId {
+        LayerId(0)
+    }
+
+    /// Adds an empty, visible and opaque layer on top of all others and returns its handle
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name to look the layer up with `layer_by_name`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// use graphic_library::graphic::Renderer;
+    /// use graphic_library::geometric::Point2D;
+    /// fn main(){
+    /// let mut renderer = Renderer::new(10, 10);
+    /// let debug = renderer.add_layer("debug");
+    /// let point = renderer.add(Point2D::new(1.0, 1.0));
+    /// renderer.set_layer(point, debug);
+    /// //Leaves the debug layer out of the export
+    /// renderer.set_layer_visible(debug, false);
+    /// renderer.draw();
+    /// }
+    /// ```
+    pub fn add_layer(&mut self, name: &str) -> LayerId {
+        let id = LayerId(self.next_layer);
+        self.next_layer += 1;
+        self.layers.push(Layer {
+            id,
+            name: name.to_owned(),
+            opacity: 1.0,
+            visible: true,
+            blend_mode: BlendMode::Normal
+        });
+        id
+    }
+
+    /// Removes a layer together with its Geometric-Objects, the default layer can not be removed
+    pub fn remove_layer(&mut self, layer: LayerId) -> bool {
+        if layer == self.default_layer() || self.layer_position(layer).is_none() {
+            return false;
+        }
+        self.layers.retain(|other| other.id != layer);
+        let removed: Vec<ShapeId> = self.shapes.iter().filter(|&(_, entry)| entry.layer == layer).map(|(&id, _)| id).collect();
+        for id in removed {
+            self.remove(id);
+        }
+        true
+    }
+
+    /// Returns the first layer with a name
+    pub fn layer_by_name(&self, name: &str) -> Option<LayerId> {
+        self.layers.iter().find(|layer| layer.name == name).map(|layer| layer.id)
+    }
+
+    /// Returns the name of a layer
+    pub fn layer_name(&self, layer: LayerId) -> Option<&str> {
+        self.find_layer(layer).map(|layer| &layer.name[..])
+    }
+
+    /// Returns all layers in compositing order, bottom first
+    pub fn layers(&self) -> Vec<LayerId> {
+        self.layers.iter().map(|layer| layer.id).collect()
+    }
+
+    /// Moves a layer to a position in the compositing order, 0 is the bottom, returns false for unknown handles
+    pub fn move_layer(&mut self, layer: LayerId, position: usize) -> bool {
+        match self.layer_position(layer) {
+            Some(old) => {
+                let layer = self.layers.remove(old);
+                let position = position.min(self.layers.len());
+                self.layers.insert(position, layer);
+                true
+            }
+            None => false
+        }
+    }
+
+    /// Sets the opacity between 0..1 a layer is composited with, returns false for unknown handles
+    pub fn set_layer_opacity(&mut self, layer: LayerId, opacity: f64) -> bool {
+        self.find_layer_mut(layer).map(|layer| layer.opacity = opacity.clamp(0.0, 1.0)).is_some()
+    }
+
+    /// Returns the opacity a layer is composited with
+    pub fn layer_opacity(&self, layer: LayerId) -> Option<f64> {
+        self.find_layer(layer).map(|layer| layer.opacity)
+    }
+
+    /// Sets whether a layer is drawn, returns false for unknown handles
+    pub fn set_layer_visible(&mut self, layer: LayerId, visible: bool) -> bool {
+        self.find_layer_mut(layer).map(|layer| layer.visible = visible).is_some()
+    }
+
+    /// Returns whether a layer is drawn
+    pub fn is_layer_visible(&self, layer: LayerId) -> Option<bool> {
+        self.find_layer(layer).map(|layer| layer.visible)
+    }
+
+    /// Sets how a layer is combined with the layers below, returns false for unknown handles
+    pub fn set_layer_blend_mode(&mut self, layer: LayerId, blend_mode: BlendMode) -> bool {
+        self.find_layer_mut(layer).map(|layer| layer.blend_mode = blend_mode).is_some()
+    }
+
+    /// Returns how a layer is combined with the layers below
+    pub fn layer_blend_mode(&self, layer: LayerId) -> Option<BlendMode> {
+        self.find_layer(layer).map(|layer| layer.blend_mode)
+    }
+
+    /// Moves a Geometric-Object into a layer, returns false for unknown handles
+    pub fn set_layer(&mut self, id: ShapeId, layer: LayerId) -> bool {
+        if self.layer_position(layer).is_none() {
+            return false;
+        }
+        match self.shapes.get_mut(&id) {
+            Some(entry) => {
+                entry.layer = layer;
+                true
+            }
+            None => false
+        }
+    }
+
+    /// Returns the layer of a Geometric-Object
+    pub fn layer(&self, id: ShapeId) -> Option<LayerId> {
+        self.shapes.get(&id).map(|entry| entry.layer)
+    }
+
+    fn layer_position(&self, layer: LayerId) -> Option<usize> {
+        self.layers.iter().position(|other| other.id == layer)
+    }
+
+    fn find_layer(&self, layer: LayerId) -> Option<&Layer> {
+        self.layers.iter().find(|other| other.id == layer)
+    }
+
+    fn find_layer_mut(&mut self, layer: LayerId) -> Option<&mut Layer> {
+        self.layers.iter_mut().find(|other| other.id == layer)
+    }
+
+    /// Adds a Geometric-Object on top of the scene and returns its handle
+    pub fn add<G: Geometric2D + 'static>(&mut self, geo: G) -> ShapeId {
+        self.add_boxed(Box::new(geo))
+    }
+
+    /// Adds a boxed Geometric-Object on top of the default layer and returns its handle
+    pub fn add_boxed(&mut self, geo: Box<dyn Geometric2D>) -> ShapeId {
+        let id = ShapeId(self.next_id);
+        self.next_id += 1;
+        if let Some(bounds) = geo.bounding_box() {
+            self.index.get_mut().insert(id, bounds);
+        }
+        self.shapes.insert(id, Entry { geo, layer: LayerId(0), z_index: 0, order: self.front });
+        self.front += 1;
+        id
+    }
+
+    /// Returns the Geometric-Object of a handle
+    pub fn get(&self, id: ShapeId) -> Option<&dyn Geometric2D> {
+        self.shapes.get(&id).map(|entry| &*entry.geo)
+    }
+
+    /// Returns the Geometric-Object of a handle mutably, e.g. to transform it
+    ///
+    /// The next query refits the new bounds of the Geometric-Object into the spatial index.
+    pub fn get_mut(&mut self, id: ShapeId) -> Option<&mut (dyn Geometric2D + 'static)> {
+        let entry = self.shapes.get_mut(&id)?;
+        self.changed.get_mut().insert(id);
+        Some(&mut *entry.geo)
+    }
+
+    /// Removes a Geometric-Object from the scene and returns it
+    pub fn remove(&mut self, id: ShapeId) -> Option<Box<dyn Geometric2D>> {
+        self.index.get_mut().remove(id);
+        self.changed.get_mut().remove(&id);
+        self.shapes.remove(&id).map(|entry| entry.geo)
+    }
+
+    /// Replaces a Geometric-Object keeping its handle and position in the drawing order, returns the old one
+    pub fn replace<G: Geometric2D + 'static>(&mut self, id: ShapeId, geo: G) -> Option<Box<dyn Geometric2D>> {
+        self.changed.get_mut().insert(id);
+        match self.shapes.get_mut(&id) {
+            Some(old) => Some(std::mem::replace(&mut old.geo, Box::new(geo))),
+            None => None
+        }
+    }
+
+    /// Sets the z-index of a Geometric-Object within its layer, higher z-indices are drawn on top, returns false for unknown handles
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Handle of the Geometric-Object
+    /// * `z_index` - Z-index of the Geometric-Object within its layer, new shapes start at 0
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// use graphic_library::graphic::Renderer;
+    /// use graphic_library::geometric::Point2D;
+    /// fn main(){
+    /// let mut renderer = Renderer::new(10, 10);
+    /// let background = renderer.add(Point2D::new(1.0, 1.0));
+    /// let foreground = renderer.add(Point2D::new(1.0, 1.0));
+    /// //Draws the first Point on top although it was added first
+    /// renderer.set_z_index(background, 1);
+    /// }
+    /// ```
+    pub fn set_z_index(&mut self, id: ShapeId, z_index: i32) -> bool {
+        match self.shapes.get_mut(&id) {
+            Some(entry) => {
+                entry.z_index = z_index;
+                true
+            }
+            None => false
+        }
+    }
+
+    /// Returns the z-index of a Geometric-Object within its layer
+    pub fn z_index(&self, id: ShapeId) -> Option<i32> {
+        self.shapes.get(&id).map(|entry| entry.z_index)
+    }
+
+    /// Moves a Geometric-Object on top of all others with the same z-index, returns false for unknown handles
+    pub fn bring_to_front(&mut self, id: ShapeId) -> bool {
+        match self.shapes.get_mut(&id) {
+            Some(entry) => {
+                entry.order = self.front;
+                self.front += 1;
+                true
+            }
+            None => false
+        }
+    }
+
+    /// Moves a Geometric-Object below all others with the same z-index, returns false for unknown handles
+    pub fn send_to_back(&mut self, id: ShapeId) -> bool {
+        match self.shapes.get_mut(&id) {
+            Some(entry) => {
+                self.back -= 1;
+                entry.order = self.back;
+                true
+            }
+            None => false
+        }
+    }
+
+    /// Returns all Geometric-Objects in drawing order, bottom first, including those of hidden layers
+    pub fn iter(&self) -> impl Iterator<Item = (ShapeId, &dyn Geometric2D)> {
+        self.drawing_order().into_iter().map(move |id| (id, &*self.shapes[&id].geo))
+    }
+
+    /// Returns the handles sorted by layer and z-index, keeping the insertion order within a z-index
+    fn drawing_order(&self) -> Vec<ShapeId> {
+        let mut order: Vec<ShapeId> = self.shapes.keys().cloned().collect();
+        self.sort_for_drawing(&mut order);
+        order
+    }
+
+    /// Sorts handles into drawing order, bottom first
+    fn sort_for_drawing(&self, ids: &mut [ShapeId]) {
+        ids.sort_by_cached_key(|id| {
+            let entry = &self.shapes[id];
+            (self.layer_position(entry.layer), entry.z_index, entry.order)
+        });
+    }
+
+    /// Returns the box enclosing all Geometric-Objects of visible layers or `None` if there are none
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// use graphic_library::graphic::Renderer;
+    /// use graphic_library::geometric::{Circle2D, Point2D};
+    /// fn main(){
+    /// let mut renderer = Renderer::new(100, 100);
+    /// renderer.add(Circle2D::new(10.0, Point2D::new(50.0, 50.0)));
+    /// renderer.add(Point2D::new(90.0, 5.0));
+    /// let bounds = renderer.bounds().unwrap();
+    /// assert_eq!((40.0, 5.0, 90.0, 60.0), (bounds.min_x, bounds.min_y, bounds.max_x, bounds.max_y));
+    /// }
+    /// ```
+    pub fn bounds(&self) -> Option<BoundingBox> {
+        let visible: Vec<LayerId> = self.layers.iter().filter(|layer| layer.visible).map(|layer| layer.id).collect();
+        BoundingBox::union_all(self.shapes
+                                   .values()
+                                   .filter(|entry| visible.contains(&entry.layer))
+                                   .filter_map(|entry| entry.geo.bounding_box()))
+    }
+
+    /// Returns the Geometric-Objects of visible layers whose bounds intersect a rectangle, in drawing order
+    ///
+    /// # Arguments
+    ///
+    /// * `rect` - Rectangle to search in
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// use graphic_library::graphic::Renderer;
+    /// use graphic_library::geometric::{BoundingBox, Point2D};
+    /// fn main(){
+    /// let mut renderer = Renderer::new(100, 100);
+    /// let inside = renderer.add(Point2D::new(10.0, 10.0));
+    /// renderer.add(Point2D::new(90.0, 90.0));
+    /// assert_eq!(vec![inside], renderer.query_rect(&BoundingBox::new(0.0, 0.0, 50.0, 50.0)));
+    /// }
+    /// ```
+    pub fn query_rect(&self, rect: &BoundingBox) -> Vec<ShapeId> {
+        let mut hits = self.spatial_index().query(rect);
+        hits.retain(|id| self.is_layer_visible(self.shapes[id].layer) == Some(true));
+        self.sort_for_drawing(&mut hits);
+        hits
+    }
+
+    /// Returns the Geometric-Object of a visible layer closest to a position
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - X-Coordinate of the position
+    /// * `y` - Y-Coordinate of the position
+    ///
+    pub fn nearest(&self, x: f64, y: f64) -> Option<ShapeId> {
+        let p = Point2D::new(x, y);
+        let distance = |id: ShapeId| {
+            let entry = &self.shapes[&id];
+            if self.is_layer_visible(entry.layer) == Some(true) {
+                entry.geo.distance(&p)
+            } else {
+                f64::INFINITY
+            }
+        };
+        self.spatial_index().nearest(x, y, &distance).map(|(id, _)| id)
+    }
+
+    /// Returns the spatial index after refitting the bounds of the shapes handed out mutably
+    fn spatial_index(&self) -> Ref<'_, SpatialIndex> {
+        let mut changed = self.changed.borrow_mut();
+        if !changed.is_empty() {
+            let mut index = self.index.borrow_mut();
+            for id in changed.drain() {
+                match self.shapes.get(&id).and_then(|entry| entry.geo.bounding_box()) {
+                    Some(bounds) => index.insert(id, bounds),
+                    None => index.remove(id)
+                }
+            }
+        }
+        self.index.borrow()
+    }
+
+    /// Returns the topmost Geometric-Object of a visible layer at a position
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - X-Coordinate of the position
+    /// * `y` - Y-Coordinate of the position
+    /// * `tolerance` - Distance the position may lie outside of a Geometric-Object, e.g. to hit thin lines
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// use graphic_library::graphic::Renderer;
+    /// use graphic_library::geometric::{Circle2D, Line2D, Point2D};
+    /// fn main(){
+    /// let mut renderer = Renderer::new(100, 100);
+    /// let circle = renderer.add(Circle2D::new(10.0, Point2D::new(50.0, 50.0)));
+    /// let line = renderer.add(Line2D::new(Point2D::new(0.0, 50.0), Point2D::new(100.0, 50.0)));
+    /// assert_eq!(Some(line), renderer.pick(45.0, 51.0, 2.0));
+    /// assert_eq!(Some(circle), renderer.pick(45.0, 55.0, 2.0));
+    /// assert_eq!(None, renderer.pick(5.0, 5.0, 2.0));
+    /// }
+    /// ```
+    pub fn pick(&self, x: f64, y: f64, tolerance: f64) -> Option<ShapeId> {
+        self.pick_all(x, y, tolerance).into_iter().next()
+    }
+
+    /// Returns all Geometric-Objects of visible layers at a position, topmost first
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - X-Coordinate of the position
+    /// * `y` - Y-Coordinate of the position
+    /// * `tolerance` - Distance the position may lie outside of a Geometric-Object, e.g. to hit thin lines
+    ///
+    pub fn pick_all(&self, x: f64, y: f64, tolerance: f64) -> Vec<ShapeId> {
+        let p = Point2D::new(x, y);
+        let mut hits = self.query_rect(&BoundingBox::new(x, y, x, y).expand(tolerance));
+        hits.retain(|id| {
+            let geo = &self.shapes[id].geo;
+            geo.contains(&p) || geo.distance(&p) <= tolerance
+        });
+        hits.reverse();
+        hits
     }
+
+    /// Returns the number of Geometric-Objects in the scene
+    pub fn len(&self) -> usize {
+        self.shapes.len()
+    }
+
+    /// Returns whether the scene is empty
+    pub fn is_empty(&self) -> bool {
+        self.shapes.is_empty()
+    }
+
     pub fn save_as(&self, filename: String) {
-        raster::save(&self.image, &*filename);
+        raster::save(&self.framebuffer.to_image(), &filename);
     }
+    /// Draws the outlines of all Geometric-Objects over the current pixels
     pub fn draw_outline(&mut self) {
-        for v in &self.vertices {
-            v.borrow_mut().draw_outline(&mut self.image);
-        }
+        self.draw_with(&|geo, canvas| geo.draw_outline(canvas));
     }
+    /// Draws all Geometric-Objects over the current pixels, use `render_frame` to start from the Background
     pub fn draw(&mut self) {
-        for v in &self.vertices {
-            v.borrow_mut().draw(&mut self.image);
+        self.draw_with(&|geo, canvas| geo.draw(canvas));
+    }
+    /// Draws the anti-aliased outlines of all Geometric-Objects over the current pixels
+    pub fn draw_outline_aa(&mut self) {
+        self.draw_with(&|geo, canvas| geo.draw_outline_aa(canvas));
+    }
+    /// Draws all Geometric-Objects anti-aliased over the current pixels
+    pub fn draw_aa(&mut self) {
+        self.draw_with(&|geo, canvas| geo.draw_aa(canvas));
+    }
+
+    /// Draws the visible layers bottom to top with the anti-aliasing of the Renderer
+    fn draw_with(&mut self, draw: &(dyn Fn(&dyn Geometric2D, &mut dyn Canvas) + Sync)) {
+        let (width, height) = (self.framebuffer.width(), self.framebuffer.height());
+        let order = self.shapes_in_view(width, height);
+        let view = self.world_to_screen();
+        let tiled = self.tiled();
+        let layers = Renderer::visible_layers(&self.layers, &self.shapes, &order);
+        Renderer::draw_scene(&layers, &mut self.framebuffer, self.hdr.as_mut(), &view, self.depth.as_mut(), &self.antialiasing, tiled, draw);
+        self.resolve();
+    }
+
+    /// Returns the visible layers bottom to top with their shapes in drawing order
+    fn visible_layers<'a>(layers: &'a [Layer], shapes: &'a HashMap<ShapeId, Entry>, order: &[ShapeId]) -> Vec<(&'a Layer, Vec<&'a dyn Geometric2D>)> {
+        layers.iter()
+            .filter(|layer| layer.visible)
+            .map(|layer| (layer, order.iter()
+                .map(|id| &shapes[id])
+                .filter(|entry| entry.layer == layer.id)
+                .map(|entry| &*entry.geo)
+                .collect()))
+            .collect()
+    }
+
+    /// Draws layers into the Framebuffer, or the HdrBuffer if there is one, with an anti-aliasing mode
+    #[allow(clippy::too_many_arguments)]
+    fn draw_scene(layers: &[(&Layer, Vec<&dyn Geometric2D>)], framebuffer: &mut Framebuffer, hdr: Option<&mut HdrBuffer>, view: &Transform2D,
+                  depth: Option<&mut DepthBuffer>, antialiasing: &Antialiasing, tiled: bool, draw: &(dyn Fn(&dyn Geometric2D, &mut dyn Canvas) + Sync)) {
+        let (width, height) = (framebuffer.width(), framebuffer.height());
+        match *antialiasing {
+            Antialiasing::Supersampling { factor, filter } if factor > 1 => {
+                let view = view.then(&Transform2D::scaling(factor as f64, factor as f64));
+                //Depth is tested per sample, the given DepthBuffer is left untouched
+                let mut depth = depth.map(|_| DepthBuffer::new(width * factor as i32, height * factor as i32));
+                match hdr {
+                    Some(hdr) => antialiasing::supersample(hdr, factor, filter, |samples| {
+                        Renderer::draw_layers(layers, &mut Target::Hdr(samples), Some(&view), depth.as_mut(), tiled, draw)
+                    }),
+                    None => antialiasing::supersample(framebuffer, factor, filter, |samples| {
+                        Renderer::draw_layers(layers, &mut Target::Ldr(samples), Some(&view), depth.as_mut(), tiled, draw)
+                    })
+                }
+            }
+            Antialiasing::Multisampling(ref pattern) => {
+                let multisampled = |geo: &dyn Geometric2D, canvas: &mut dyn Canvas| antialiasing::multisample(geo, canvas, pattern, draw);
+                let draw_samples = |samples: &mut MultisampleBuffer| {
+                    Renderer::draw_layers(layers, &mut Target::Samples(samples), Some(view), depth, tiled, &multisampled)
+                };
+                match hdr {
+                    Some(hdr) => antialiasing::resolve_samples(hdr, pattern.len(), draw_samples),
+                    None => antialiasing::resolve_samples(framebuffer, pattern.len(), draw_samples)
+                }
+            }
+            _ => {
+                let mut target = match hdr {
+                    Some(hdr) => Target::Hdr(hdr),
+                    None => Target::Ldr(framebuffer)
+                };
+                Renderer::draw_layers(layers, &mut target, Some(view), depth, tiled, draw)
+            }
+        }
+    }
+
+    /// Draws layers bottom to top, compositing translucent or blended ones from an offscreen buffer of the kind of the target
+    fn draw_layers(layers: &[(&Layer, Vec<&dyn Geometric2D>)], target: &mut Target, view: Option<&Transform2D>,
+                   mut depth: Option<&mut DepthBuffer>, tiled: bool, draw: &(dyn Fn(&dyn Geometric2D, &mut dyn Canvas) + Sync)) {
+        //Shapes already given in pixels are drawn without copying them
+        let view = view.filter(|view| **view != Transform2D::identity());
+        for &(layer, ref shapes) in layers {
+            //The shapes stay untouched, the Camera and Origin only apply to the drawn copies
+            let transformed: Vec<Box<dyn Geometric2D>> = match view {
+                Some(view) => shapes.iter().map(|geo| {
+                    let mut geo = geo.box_clone();
+                    geo.apply_view(view);
+                    geo
+                }).collect(),
+                None => Vec::new()
+            };
+            let shapes: Vec<&dyn Geometric2D> = if view.is_some() { transformed.iter().map(|geo| &**geo).collect() } else { shapes.clone() };
+            if layer.is_direct() {
+                Renderer::draw_layer(&shapes, target, depth.as_deref_mut(), tiled, draw);
+                continue;
+            }
+            let depth = depth.as_deref_mut();
+            match *target {
+                Target::Ldr(ref mut framebuffer) => Renderer::draw_offscreen(&mut **framebuffer, layer, |offscreen| {
+                    Renderer::draw_layer(&shapes, &mut Target::Ldr(offscreen), depth, tiled, draw)
+                }),
+                Target::Hdr(ref mut hdr) => Renderer::draw_offscreen(&mut **hdr, layer, |offscreen| {
+                    Renderer::draw_layer(&shapes, &mut Target::Hdr(offscreen), depth, tiled, draw)
+                }),
+                Target::Samples(ref mut samples) => Renderer::draw_offscreen(&mut **samples, layer, |offscreen| {
+                    Renderer::draw_layer(&shapes, &mut Target::Samples(offscreen), depth, tiled, draw)
+                })
+            }
+        }
+    }
+
+    /// Draws a layer into a transparent buffer of the same kind as `buffer` and composites it over `buffer`
+    fn draw_offscreen<B: LayerBuffer, F: FnOnce(&mut B)>(buffer: &mut B, layer: &Layer, draw_layer: F) {
+        let mut offscreen = buffer.blank(buffer.width(), buffer.height());
+        draw_layer(&mut offscreen);
+        buffer.composite_layer(&offscreen, 0, 0, layer.opacity, layer.blend_mode);
+    }
+
+    /// Returns the handles of the shapes overlapping an image of a given size in drawing order
+    fn shapes_in_view(&self, width: i32, height: i32) -> Vec<ShapeId> {
+        let screen = BoundingBox::new(0.0, 0.0, width as f64, height as f64);
+        let view = self.view(width, height);
+        match view.inverse() {
+            //Anti-aliasing may paint a pixel beyond the bounds
+            Some(to_world) => self.query_rect(&screen.transform(&to_world).expand(2.0 / view.scale_factor())),
+            None => Vec::new()
+        }
+    }
+
+    /// Draws the Geometric-Objects of a layer, depth testing only against each other
+    #[cfg_attr(not(feature = "parallel"), allow(unused_variables))]
+    fn draw_layer(shapes: &[&dyn Geometric2D], target: &mut Target, mut depth: Option<&mut DepthBuffer>, tiled: bool,
+                  draw: &(dyn Fn(&dyn Geometric2D, &mut dyn Canvas) + Sync)) {
+        if let Some(ref mut depth) = depth {
+            depth.clear();
+        }
+        #[cfg(feature = "parallel")]
+        {
+            if tiled {
+                match *target {
+                    Target::Ldr(ref mut framebuffer) => return tiles::draw_tiled(&mut **framebuffer, shapes, depth, draw),
+                    Target::Hdr(ref mut hdr) => return tiles::draw_tiled(&mut **hdr, shapes, depth, draw),
+                    //Samples are kept for the whole image, so multisampled layers are drawn serially
+                    Target::Samples(_) => {}
+                }
+            }
+        }
+        let canvas = target.canvas();
+        match depth {
+            Some(depth) => {
+                let mut canvas = DepthCanvas::new(canvas, depth);
+                for geo in shapes {
+                    draw(*geo, &mut canvas);
+                }
+            }
+            None => {
+                for geo in shapes {
+                    draw(*geo, canvas);
+                }
+            }
+        }
+    }
+
+    /// Sets whether draws split the image into tiles rasterized on the rayon thread pool
+    ///
+    /// The pixels are the same as when drawing serially.
+    #[cfg(feature = "parallel")]
+    pub fn set_parallel(&mut self, parallel: bool) {
+        self.parallel = parallel;
+    }
+
+    /// Returns whether draws rasterize tiles on the rayon thread pool
+    #[cfg(feature = "parallel")]
+    pub fn is_parallel(&self) -> bool {
+        self.parallel
+    }
+
+    #[cfg(feature = "parallel")]
+    fn tiled(&self) -> bool {
+        self.parallel
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    fn tiled(&self) -> bool {
+        false
+    }
+
+    /// Draws all Geometric-Objects over another Canvas instead of the own Framebuffer
+    ///
+    /// Layers, Camera, Origin, anti-aliasing and depth testing apply as in `draw`, sized to the Canvas.
+    /// The drawing happens in an 8-bit copy of the writable pixels of the Canvas, the pixels that
+    /// changed are written back with `set_pixel`.
+    ///
+    /// # Arguments
+    ///
+    /// * `canvas` - Canvas to draw over
+    ///
+    pub fn draw_into(&self, canvas: &mut dyn Canvas) {
+        let (x0, y0, x1, y1) = layer::writable_rect(canvas);
+        if x0 >= x1 || y0 >= y1 {
+            return;
+        }
+        let (width, height) = (canvas.width(), canvas.height());
+        let mut framebuffer = Framebuffer::new(width, height);
+        framebuffer.set_linear_blending(canvas.linear_blending());
+        for y in y0..y1 {
+            for x in x0..x1 {
+                if let Some(color) = canvas.get_pixel(x, y) {
+                    framebuffer.set_pixel(x, y, &color);
+                }
+            }
         }
+        let before = framebuffer.clone();
+        let order = self.shapes_in_view(width, height);
+        let layers = Renderer::visible_layers(&self.layers, &self.shapes, &order);
+        let mut depth = self.depth.as_ref().map(|_| DepthBuffer::new(width, height));
+        Renderer::draw_scene(&layers, &mut framebuffer, None, &self.view(width, height), depth.as_mut(), &self.antialiasing, self.tiled(),
+                             &|geo, canvas| geo.draw(canvas));
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let (old, new) = (before.get_pixel(x, y).unwrap(), framebuffer.get_pixel(x, y).unwrap());
+                if (old.r, old.g, old.b, old.a) != (new.r, new.g, new.b, new.a) {
+                    canvas.set_pixel(x, y, &new);
+                }
+            }
+        }
+    }
+
+    /// Tone maps the HdrBuffer into the Framebuffer, e.g. after drawing into it directly
+    pub fn resolve(&mut self) {
+        if let Some(ref hdr) = self.hdr {
+            hdr.tone_map_into(&mut self.framebuffer, self.tone_mapping, self.exposure);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geometric::{Circle2D, Line2D, Triangle2D};
+    use graphic::{DownsampleFilter, SamplePattern};
+
+    fn ids(renderer: &Renderer) -> Vec<ShapeId> {
+        renderer.iter().map(|(id, _)| id).collect()
+    }
+
+    #[test]
+    fn add_get_remove() {
+        let mut renderer = Renderer::new(10, 10);
+        let a = renderer.add(Point2D::new(1.0, 1.0));
+        let b = renderer.add(Point2D::new(2.0, 2.0));
+        assert_eq!(vec![a, b], ids(&renderer));
+        renderer.get_mut(a).unwrap().transform(1.0, 0.0);
+        assert!(renderer.remove(a).is_some());
+        assert!(renderer.remove(a).is_none());
+        assert!(renderer.get(a).is_none());
+        assert_eq!(vec![b], ids(&renderer));
+        assert!(renderer.replace(b, Point2D::new(3.0, 3.0)).is_some());
+        assert_eq!(1, renderer.len());
+    }
+
+    #[test]
+    fn reorder() {
+        let mut renderer = Renderer::new(10, 10);
+        let a = renderer.add(Point2D::new(1.0, 1.0));
+        let b = renderer.add(Point2D::new(2.0, 2.0));
+        let c = renderer.add(Point2D::new(3.0, 3.0));
+        assert!(renderer.bring_to_front(a));
+        assert_eq!(vec![b, c, a], ids(&renderer));
+        assert!(renderer.send_to_back(c));
+        assert_eq!(vec![c, b, a], ids(&renderer));
+    }
+
+    #[test]
+    fn later_shapes_are_drawn_on_top() {
+        let mut renderer = Renderer::new(4, 4);
+        renderer.add(Point2D::new_color(1.0, 1.0, Color::red()));
+        renderer.add(Point2D::new_color(1.0, 1.0, Color::blue()));
+        renderer.draw();
+        assert_eq!(255, renderer.framebuffer().get_pixel(1, 1).unwrap().b);
+    }
+
+    #[test]
+    fn render_frame_starts_from_background() {
+        let mut renderer = Renderer::new(4, 4);
+        renderer.set_background(Background::Color(Color::green()));
+        let point = renderer.add(Point2D::new_color(1.0, 1.0, Color::red()));
+        renderer.render_frame();
+        renderer.get_mut(point).unwrap().transform(1.0, 0.0);
+        renderer.render_frame();
+        assert_eq!(255, renderer.framebuffer().get_pixel(1, 1).unwrap().g);
+        assert_eq!(255, renderer.framebuffer().get_pixel(2, 1).unwrap().r);
+    }
+
+    #[test]
+    fn z_index_orders_drawing() {
+        let mut renderer = Renderer::new(4, 4);
+        let a = renderer.add(Point2D::new_color(1.0, 1.0, Color::red()));
+        let b = renderer.add(Point2D::new_color(1.0, 1.0, Color::blue()));
+        let c = renderer.add(Point2D::new_color(1.0, 1.0, Color::green()));
+        assert!(renderer.set_z_index(a, 1));
+        assert!(renderer.set_z_index(c, -1));
+        assert_eq!(vec![c, b, a], ids(&renderer));
+        renderer.draw();
+        assert_eq!(255, renderer.framebuffer().get_pixel(1, 1).unwrap().r);
+    }
+
+    #[test]
+    fn layers_are_composited() {
+        let mut renderer = Renderer::new(4, 4);
+        let overlay = renderer.add_layer("overlay");
+        assert_eq!(Some(overlay), renderer.layer_by_name("overlay"));
+        let red = renderer.add(Point2D::new_color(1.0, 1.0, Color::red()));
+        let blue = renderer.add(Point2D::new_color(1.0, 1.0, Color::blue()));
+        assert!(renderer.set_layer(red, overlay));
+        assert_eq!(vec![blue, red], ids(&renderer));
+        renderer.set_layer_visible(overlay, false);
+        renderer.draw();
+        assert_eq!((0, 255), (renderer.framebuffer().get_pixel(1, 1).unwrap().r, renderer.framebuffer().get_pixel(1, 1).unwrap().b));
+        renderer.set_layer_visible(overlay, true);
+        renderer.set_layer_opacity(overlay, 0.5);
+        renderer.draw();
+        let color = renderer.framebuffer().get_pixel(1, 1).unwrap();
+        assert_eq!((128, 0, 128), (color.r, color.g, color.b));
+        renderer.set_layer_opacity(overlay, 1.0);
+        renderer.set_layer_blend_mode(overlay, BlendMode::Multiply);
+        renderer.draw();
+        let color = renderer.framebuffer().get_pixel(1, 1).unwrap();
+        assert_eq!((0, 0, 0), (color.r, color.g, color.b));
+        assert!(renderer.remove_layer(overlay));
+        assert_eq!(vec![blue], ids(&renderer));
+        assert!(!renderer.remove_layer(renderer.default_layer()));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn layers_are_composited_in_the_space_of_the_target() {
+        //Four additive points add up to 4.0 in the layer, which stays unclamped at half opacity
+        let mut renderer = Renderer::new_hdr(2, 2);
+        renderer.hdr_buffer_mut().unwrap().set_blend_mode(BlendMode::Additive);
+        let glow = renderer.add_layer("glow");
+        renderer.set_layer_opacity(glow, 0.5);
+        for _ in 0..4 {
+            let point = renderer.add(Point2D::new_color(0.0, 0.0, Color::red()));
+            renderer.set_layer(point, glow);
+        }
+        renderer.render_frame();
+        assert_eq!(2.0, renderer.hdr_buffer().unwrap().get(0, 0).unwrap()[0]);
+        //Screening two half bright grays in linear light emits three quarters of the light
+        let mut renderer = Renderer::new(2, 2);
+        renderer.set_linear_blending(true);
+        renderer.set_background(Background::Color(Color::rgb(188, 188, 188)));
+        let screen = renderer.add_layer("screen");
+        renderer.set_layer_blend_mode(screen, BlendMode::Screen);
+        let point = renderer.add(Point2D::new_color(0.0, 0.0, Color::rgb(188, 188, 188)));
+        renderer.set_layer(point, screen);
+        renderer.render_frame();
+        assert_eq!(225, renderer.framebuffer().get_pixel(0, 0).unwrap().r);
+    }
+
+    #[test]
+    fn spatial_queries_follow_changes() {
+        let mut renderer = Renderer::new(100, 100);
+        let a = renderer.add(Point2D::new(10.0, 10.0));
+        let b = renderer.add(Point2D::new(80.0, 80.0));
+        assert_eq!(Some(a), renderer.nearest(20.0, 20.0));
+        renderer.get_mut(b).unwrap().transform(-65.0, -65.0);
+        assert_eq!(Some(b), renderer.nearest(20.0, 20.0));
+        assert_eq!(vec![a, b], renderer.query_rect(&BoundingBox::new(0.0, 0.0, 20.0, 20.0)));
+        assert_eq!(Some(b), renderer.pick(15.0, 16.0, 1.0));
+        //Reordering keeps the bounds, the hits follow the new drawing order
+        renderer.set_z_index(a, 1);
+        renderer.send_to_back(b);
+        assert!(renderer.changed.borrow().is_empty());
+        assert_eq!(vec![b, a], renderer.query_rect(&BoundingBox::new(0.0, 0.0, 20.0, 20.0)));
+        renderer.replace(a, Point2D::new(90.0, 90.0));
+        renderer.remove(b);
+        assert!(renderer.query_rect(&BoundingBox::new(0.0, 0.0, 20.0, 20.0)).is_empty());
+        assert_eq!(Some(a), renderer.nearest(20.0, 20.0));
+    }
+
+    #[test]
+    fn camera_maps_and_culls() {
+        let mut renderer = Renderer::new(10, 10);
+        renderer.add(Point2D::new_color(100.0, 100.0, Color::red()));
+        renderer.add(Point2D::new_color(101.0, 100.0, Color::blue()));
+        renderer.set_camera(Some(Camera::new(100.0, 100.0, 2.0, 0.0)));
+        assert_eq!(2, renderer.shapes_in_view(10, 10).len());
+        renderer.render_frame();
+        assert_eq!(255, renderer.framebuffer().get_pixel(5, 5).unwrap().r);
+        assert_eq!(255, renderer.framebuffer().get_pixel(7, 5).unwrap().b);
+        renderer.set_camera(Some(Camera::new(0.0, 0.0, 1.0, 0.0)));
+        assert!(renderer.shapes_in_view(10, 10).is_empty());
+        assert_eq!((100.0, 100.0), renderer.get(ShapeId(0)).map(|geo| geo.bounding_box().unwrap().center()).unwrap());
+    }
+
+    #[test]
+    fn draw_into_matches_draw() {
+        let mut renderer = Renderer::new(12, 10);
+        let overlay = renderer.add_layer("overlay");
+        let hidden = renderer.add_layer("hidden");
+        renderer.add(Triangle2D::new(Point2D::new_color(0.0, 0.0, Color::red()), Point2D::new(9.0, 2.0), Point2D::new(3.0, 8.0)));
+        let circle = renderer.add(Circle2D::new(3.0, Point2D::new_color(6.0, 5.0, Color::blue())));
+        let point = renderer.add(Point2D::new_color(1.0, 7.0, Color::green()));
+        renderer.set_layer(circle, overlay);
+        renderer.set_layer(point, hidden);
+        renderer.set_layer_opacity(overlay, 0.5);
+        renderer.set_layer_visible(hidden, false);
+        renderer.set_camera(Some(Camera::new(5.0, 4.0, 1.5, 0.3)));
+        renderer.set_origin(Origin::BottomLeft);
+        for antialiasing in [Antialiasing::Off, Antialiasing::Multisampling(SamplePattern::rotated_grid()),
+                                 Antialiasing::Supersampling { factor: 2, filter: DownsampleFilter::Box }] {
+            renderer.set_antialiasing(antialiasing);
+            renderer.clear();
+            renderer.draw();
+            let mut canvas = Framebuffer::new(12, 10);
+            renderer.background().paint(&mut canvas);
+            renderer.draw_into(&mut canvas);
+            assert_eq!(renderer.framebuffer().to_image().bytes, canvas.to_image().bytes);
+        }
+    }
+
+    /// Returns the pixels of the Framebuffer with some red in them
+    fn painted(renderer: &Renderer) -> Vec<(i32, i32)> {
+        let framebuffer = renderer.framebuffer();
+        let mut pixels = Vec::new();
+        for y in 0..framebuffer.height() {
+            for x in 0..framebuffer.width() {
+                if framebuffer.get_pixel(x, y).unwrap().r > 0 {
+                    pixels.push((x, y));
+                }
+            }
+        }
+        pixels
+    }
+
+    #[test]
+    fn filled_and_outlined_shapes_line_up() {
+        let red = |x, y| Point2D::new_color(x, y, Color::red());
+        let shapes: Vec<Box<dyn Geometric2D>> = vec![
+            Box::new(Triangle2D::new(red(3.0, 4.0), red(25.0, 8.0), red(10.0, 27.0))),
+            Box::new(Circle2D::new(8.0, red(15.0, 12.0))),
+            Box::new(Line2D::new(red(2.0, 25.0), red(28.0, 5.0)))
+        ];
+        for origin in &[Origin::TopLeft, Origin::BottomLeft] {
+            for (i, shape) in shapes.iter().enumerate() {
+                let mut renderer = Renderer::new(32, 32);
+                renderer.set_origin(*origin);
+                renderer.add_boxed(shape.clone());
+                renderer.render_frame();
+                let filled = painted(&renderer);
+                renderer.clear();
+                renderer.draw_outline();
+                let outlined = painted(&renderer);
+                //The outline consists of the pixels of the fill, among them every one next to an unpainted pixel
+                for pixel in &outlined {
+                    assert!(filled.contains(pixel), "shape {} with {:?}: outline pixel {:?} is not filled", i, origin, pixel);
+                }
+                for &(x, y) in &filled {
+                    let border = [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)].iter().any(|pixel| !filled.contains(pixel));
+                    assert!(!border || outlined.contains(&(x, y)), "shape {} with {:?}: border pixel {} {} is not outlined", i, origin, x, y);
+                }
+                //The fill covers the shape where the Origin puts it, pixel centers of Lines lie within half a diagonal
+                let to_world = renderer.screen_to_world().unwrap();
+                for &(x, y) in &filled {
+                    let (wx, wy) = to_world.apply(x as f64 + 0.5, y as f64 + 0.5);
+                    assert!(shape.distance(&Point2D::new(wx, wy)) <= 0.75, "shape {} with {:?}: pixel {} {} lies outside", i, origin, x, y);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bottom_left_origin_reaches_the_bottom_row() {
+        let mut renderer = Renderer::new(8, 8);
+        renderer.set_origin(Origin::BottomLeft);
+        //A band from y=0 to y=2 and a Point at y=0
+        renderer.add(Triangle2D::new(Point2D::new_color(0.0, 0.0, Color::red()), Point2D::new_color(8.0, 0.0, Color::red()),
+                                     Point2D::new_color(0.0, 2.0, Color::red())));
+        renderer.add(Triangle2D::new(Point2D::new_color(8.0, 0.0, Color::red()), Point2D::new_color(8.0, 2.0, Color::red()),
+                                     Point2D::new_color(0.0, 2.0, Color::red())));
+        renderer.add(Point2D::new_color(3.0, 0.0, Color::blue()));
+        renderer.render_frame();
+        let pixel = |x, y| renderer.framebuffer().get_pixel(x, y).unwrap();
+        for x in 0..8 {
+            assert_eq!((0, 255, 255), (pixel(x, 5).r, pixel(x, 6).r, pixel(x, 7).r + pixel(x, 7).b), "column {}", x);
+        }
+        assert_eq!(255, pixel(3, 7).b);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_matches_serial() {
+        let render = |parallel: bool| {
+            let mut renderer = Renderer::new(300, 200);
+            renderer.set_parallel(parallel);
+            renderer.set_depth_test(true);
+            let overlay = renderer.add_layer("overlay");
+            renderer.set_layer_opacity(overlay, 0.5);
+            renderer.add(Triangle2D::new(Point2D::new_color(10.0, 10.0, Color::red()).with_depth(1.0),
+                                         Point2D::new_color(290.0, 30.0, Color::green()),
+                                         Point2D::new_color(100.0, 190.0, Color::blue()).with_depth(-1.0)));
+            renderer.add(Circle2D::new(70.0, Point2D::new_color(150.0, 100.0, Color::white()).with_depth(0.5)));
+            let line = renderer.add(Line2D::new(Point2D::new_color(0.0, 0.0, Color::red()), Point2D::new(299.0, 199.0)));
+            renderer.set_layer(line, overlay);
+            renderer.render_frame();
+            (renderer.framebuffer().as_bytes().to_vec(), renderer.depth_buffer().unwrap().get(150, 100))
+        };
+        assert!(render(false) == render(true));
+    }
+
+    #[test]
+    fn anti_aliased_draw_modes_draw_every_shape() {
+        let circle = Circle2D::new(5.5, Point2D::new_color(8.25, 7.5, Color::red()));
+        let mut renderer = Renderer::new(16, 16);
+        renderer.add(circle.clone());
+        let mut expected = Framebuffer::new(16, 16);
+        expected.clear(&Color::black());
+        renderer.draw_aa();
+        circle.draw_aa(&mut expected);
+        assert!(renderer.framebuffer().as_bytes() == expected.as_bytes());
+        renderer.draw_outline_aa();
+        circle.draw_outline_aa(&mut expected);
+        assert!(renderer.framebuffer().as_bytes() == expected.as_bytes());
+        //The edge is blended with partial coverage
+        assert!(expected.as_bytes().chunks(4).any(|p| p[0] > 0 && p[0] < 255));
+    }
+
+    #[test]
+    fn depth_test_keeps_closest_pixel() {
+        let mut renderer = Renderer::new(4, 4);
+        renderer.set_depth_test(true);
+        renderer.add(Point2D::new_color(1.0, 1.0, Color::red()).with_depth(1.0));
+        renderer.add(Point2D::new_color(1.0, 1.0, Color::blue()).with_depth(2.0));
+        renderer.draw();
+        assert_eq!(255, renderer.framebuffer().get_pixel(1, 1).unwrap().r);
+        assert_eq!(Some(1.0), renderer.depth_buffer().unwrap().get(1, 1));
+    }
+
+    #[test]
+    fn supersampling_and_multisampling_cover_half_pixels() {
+        let modes = vec![
+            Antialiasing::Supersampling { factor: 4, filter: DownsampleFilter::Box },
+            Antialiasing::Supersampling { factor: 4, filter: DownsampleFilter::Tent },
+            Antialiasing::Multisampling(SamplePattern::grid(2))
+        ];
+        for antialiasing in modes {
+            //A rectangle starting in the middle of the third column
+            let mut renderer = Renderer::new(8, 8);
+            renderer.add(Triangle2D::new(Point2D::new_color(2.5, 0.0, Color::red()), Point2D::new_color(2.5, 8.0, Color::red()), Point2D::new_color(8.0, 0.0, Color::red())));
+            renderer.add(Triangle2D::new(Point2D::new_color(8.0, 0.0, Color::red()), Point2D::new_color(2.5, 8.0, Color::red()), Point2D::new_color(8.0, 8.0, Color::red())));
+            renderer.set_antialiasing(antialiasing.clone());
+            renderer.render_frame();
+            let pixel = |x, y| renderer.framebuffer().get_pixel(x, y).unwrap().r;
+            assert_eq!(128, pixel(2, 4), "{:?}", antialiasing);
+            assert_eq!(255, pixel(4, 4), "{:?}", antialiasing);
+            assert_eq!(0, pixel(0, 4), "{:?}", antialiasing);
+        }
+    }
+}
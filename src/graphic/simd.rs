@@ -0,0 +1,568 @@
+//! Span kernels for rows of 8-bit pixels
+//!
+//! Vector kernels exist for x86 only, SSE2 and AVX2. Other architectures, ARM included, run the scalar kernels.
+
+use graphic::canvas::{blend_bytes, blend_linear_bytes};
+
+/// Instruction set the span kernels run on
+///
+/// Every instruction set yields exactly the same bytes, the scalar kernels run on any processor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Isa {
+    /// Plain Rust, one pixel at a time
+    Scalar,
+    /// Four pixels at once in 128 bit registers
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    Sse2(Sse2),
+    /// Eight pixels at once in 256 bit registers
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    Avx2(Avx2)
+}
+
+/// Proof that the processor supports SSE2, only `Isa::detect` and `Isa::supported` create one
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sse2(());
+
+/// Proof that the processor supports AVX2, only `Isa::detect` and `Isa::supported` create one
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Avx2(());
+
+impl Isa {
+    /// Returns the fastest instruction set the processor supports
+    pub fn detect() -> Isa {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        {
+            if is_x86_feature_detected!("avx2") {
+                return Isa::Avx2(Avx2(()));
+            }
+            if is_x86_feature_detected!("sse2") {
+                return Isa::Sse2(Sse2(()));
+            }
+        }
+        Isa::Scalar
+    }
+
+    /// Returns every instruction set the processor supports, from the scalar kernels to the fastest ones
+    pub fn supported() -> Vec<Isa> {
+        #[allow(unused_mut)]
+        let mut supported = vec![Isa::Scalar];
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        {
+            if is_x86_feature_detected!("sse2") {
+                supported.push(Isa::Sse2(Sse2(())));
+            }
+            if is_x86_feature_detected!("avx2") {
+                supported.push(Isa::Avx2(Avx2(())));
+            }
+        }
+        supported
+    }
+
+    /// Returns the name of the instruction set
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Isa::Scalar => "Scalar",
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Isa::Sse2(_) => "SSE2",
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Isa::Avx2(_) => "AVX2"
+        }
+    }
+}
+
+/// Overwrites every pixel of a row of encoded pixels
+///
+/// # Arguments
+///
+/// * `isa` - Instruction set to run on
+/// * `dst` - Pixels to overwrite, four bytes each
+/// * `pixel` - Encoded pixel to write
+///
+pub fn fill(isa: Isa, dst: &mut [u8], pixel: [u8; 4]) {
+    match isa {
+        Isa::Scalar => {
+            for chunk in dst.chunks_exact_mut(4) {
+                chunk.copy_from_slice(&pixel);
+            }
+        }
+        //Safe since the tokens prove the processor supports the instruction set
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        Isa::Sse2(_) => unsafe { sse2::fill(dst, pixel) },
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        Isa::Avx2(_) => unsafe { avx2::fill(dst, pixel) }
+    }
+}
+
+/// Blends one encoded color over a row of encoded pixels with straight alpha
+///
+/// The scalar kernel blends runs of equal pixels underneath once, flat areas below translucent overlays stay cheap.
+///
+/// # Arguments
+///
+/// * `isa` - Instruction set to run on
+/// * `dst` - Pixels underneath, four bytes each with alpha last
+/// * `src` - Encoded color on top
+/// * `coverage` - Covered area of every pixel between 0..1, multiplies the alpha of `src`
+/// * `linear` - Whether to blend in linear light, which always runs scalar
+///
+pub fn blend(isa: Isa, dst: &mut [u8], src: &[u8; 4], coverage: f64, linear: bool) {
+    match isa {
+        _ if linear => reuse_runs(dst, |pixel| blend_linear_bytes(pixel, src, coverage)),
+        Isa::Scalar => reuse_runs(dst, |pixel| blend_bytes(pixel, src, coverage)),
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        Isa::Sse2(_) => unsafe { sse2::blend(dst, src, coverage) },
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        Isa::Avx2(_) => unsafe { avx2::blend(dst, src, coverage) }
+    }
+}
+
+/// Blends a row of encoded pixels over another one with straight alpha, transparent pixels on top are skipped
+///
+/// # Arguments
+///
+/// * `isa` - Instruction set to run on
+/// * `dst` - Pixels underneath, four bytes each with alpha last
+/// * `src` - Pixels on top in the same layout, as many as in `dst`
+/// * `coverage` - Opacity of `src` between 0..1, multiplies the alpha of every pixel
+/// * `linear` - Whether to blend in linear light, which always runs scalar
+///
+pub fn composite(isa: Isa, dst: &mut [u8], src: &[u8], coverage: f64, linear: bool) {
+    match isa {
+        _ if linear => composite_scalar(dst, src, &|pixel, over| blend_linear_bytes(pixel, over, coverage)),
+        Isa::Scalar => composite_scalar(dst, src, &|pixel, over| blend_bytes(pixel, over, coverage)),
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        Isa::Sse2(_) => unsafe { sse2::composite(dst, src, coverage) },
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        Isa::Avx2(_) => unsafe { avx2::composite(dst, src, coverage) }
+    }
+}
+
+/// Overwrites a row of encoded pixels with two encoded colors mixed in sRGB, like `interpolate_color`
+///
+/// # Arguments
+///
+/// * `isa` - Instruction set to run on
+/// * `dst` - Pixels to overwrite, four bytes each with alpha last
+/// * `from` - Encoded color at `t` = 0
+/// * `to` - Encoded color at `t` = 1
+/// * `t` - Interpolation value of the first pixel and its change per pixel, clamped to 0..1 per pixel
+///
+pub fn gradient(isa: Isa, dst: &mut [u8], from: &[u8; 4], to: &[u8; 4], t: (f64, f64)) {
+    match isa {
+        Isa::Scalar => gradient_scalar(dst, 0, from, to, t),
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        Isa::Sse2(_) => unsafe { sse2::gradient(dst, from, to, t) },
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        Isa::Avx2(_) => unsafe { avx2::gradient(dst, from, to, t) }
+    }
+}
+
+/// Blends every pixel of a row, runs of equal pixels underneath are blended once
+#[inline(always)]
+fn reuse_runs<F: FnMut(&mut [u8])>(dst: &mut [u8], mut blend: F) {
+    let mut last: Option<([u8; 4], [u8; 4])> = None;
+    for pixel in dst.chunks_exact_mut(4) {
+        let before = [pixel[0], pixel[1], pixel[2], pixel[3]];
+        match last {
+            Some((under, over)) if under == before => pixel.copy_from_slice(&over),
+            _ => {
+                blend(pixel);
+                last = Some((before, [pixel[0], pixel[1], pixel[2], pixel[3]]));
+            }
+        }
+    }
+}
+
+/// Blends the pixels of `src` with some alpha over the pixels of `dst`
+fn composite_scalar(dst: &mut [u8], src: &[u8], blend: &dyn Fn(&mut [u8], &[u8; 4])) {
+    for (pixel, over) in dst.chunks_exact_mut(4).zip(src.chunks_exact(4)) {
+        if over[3] > 0 {
+            blend(pixel, &[over[0], over[1], over[2], over[3]]);
+        }
+    }
+}
+
+/// Writes a gradient into a row of pixels whose first pixel is pixel `first` of the gradient
+fn gradient_scalar(dst: &mut [u8], first: usize, from: &[u8; 4], to: &[u8; 4], t: (f64, f64)) {
+    let (t0, dt) = t;
+    for (i, pixel) in dst.chunks_exact_mut(4).enumerate() {
+        let t = (t0 + (first + i) as f64 * dt).clamp(0.0, 1.0);
+        let channel = |a: u8, b: u8| (((1.0 - t) * (a as f64 / 255.0) + t * (b as f64 / 255.0)) * 255.0).round().clamp(0.0, 255.0) as u8;
+        pixel[0] = channel(from[0], to[0]);
+        pixel[1] = channel(from[1], to[1]);
+        pixel[2] = channel(from[2], to[2]);
+        pixel[3] = ((1.0 - t) * from[3] as f64 + t * to[3] as f64).round().clamp(0.0, 255.0) as u8;
+    }
+}
+
+/// Kernels holding a channel of four pixels per register, evaluating the same operations in the same order
+/// as the scalar kernels, the pixels left over are handed to them
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod sse2 {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::*;
+    use graphic::canvas::blend_bytes;
+
+    /// Bytes of four pixels
+    const CHUNK: usize = 16;
+
+    /// Returns the four channels of four pixels as floats
+    #[inline]
+    #[target_feature(enable = "sse2")]
+    unsafe fn channels(pixels: __m128i) -> [__m128; 4] {
+        let byte = _mm_set1_epi32(0xFF);
+        [_mm_cvtepi32_ps(_mm_and_si128(pixels, byte)),
+         _mm_cvtepi32_ps(_mm_and_si128(_mm_srli_epi32(pixels, 8), byte)),
+         _mm_cvtepi32_ps(_mm_and_si128(_mm_srli_epi32(pixels, 16), byte)),
+         _mm_cvtepi32_ps(_mm_srli_epi32(pixels, 24))]
+    }
+
+    /// Rounds half away from zero like `f32::round`, clamps to 0..255 and returns the integers
+    #[inline]
+    #[target_feature(enable = "sse2")]
+    unsafe fn round_ps(v: __m128) -> __m128i {
+        //SSE2 has no rounding instruction, converting back and forth truncates the values blended here
+        let whole = _mm_cvtepi32_ps(_mm_cvttps_epi32(v));
+        let up = _mm_cmpge_ps(_mm_sub_ps(v, whole), _mm_set1_ps(0.5));
+        let rounded = _mm_add_ps(whole, _mm_and_ps(up, _mm_set1_ps(1.0)));
+        _mm_cvttps_epi32(_mm_min_ps(_mm_max_ps(rounded, _mm_setzero_ps()), _mm_set1_ps(255.0)))
+    }
+
+    /// Rounds half away from zero like `f64::round`, clamps to 0..255 and returns the integers in the lower half
+    #[inline]
+    #[target_feature(enable = "sse2")]
+    unsafe fn round_pd(v: __m128d) -> __m128i {
+        let whole = _mm_cvtepi32_pd(_mm_cvttpd_epi32(v));
+        let up = _mm_cmpge_pd(_mm_sub_pd(v, whole), _mm_set1_pd(0.5));
+        let rounded = _mm_add_pd(whole, _mm_and_pd(up, _mm_set1_pd(1.0)));
+        _mm_cvttpd_epi32(_mm_min_pd(_mm_max_pd(rounded, _mm_setzero_pd()), _mm_set1_pd(255.0)))
+    }
+
+    /// Packs four channels of integers between 0..255 into pixels
+    #[inline]
+    #[target_feature(enable = "sse2")]
+    unsafe fn pack(r: __m128i, g: __m128i, b: __m128i, a: __m128i) -> __m128i {
+        _mm_or_si128(_mm_or_si128(r, _mm_slli_epi32(g, 8)), _mm_or_si128(_mm_slli_epi32(b, 16), _mm_slli_epi32(a, 24)))
+    }
+
+    /// Returns a channel blended like `blend_bytes`, `weighted` is the channel on top multiplied with its alpha
+    #[inline]
+    #[target_feature(enable = "sse2")]
+    unsafe fn over(under: __m128, weighted: __m128, da: __m128, keep: __m128, oa: __m128) -> __m128i {
+        round_ps(_mm_div_ps(_mm_add_ps(weighted, _mm_mul_ps(_mm_mul_ps(under, da), keep)), oa))
+    }
+
+    /// Blends four pixels on top over four pixels underneath
+    ///
+    /// # Arguments
+    ///
+    /// * `under` - Pixels underneath
+    /// * `weighted` - Color channels on top multiplied with `sa`
+    /// * `sa` - Alpha on top multiplied with the coverage
+    ///
+    #[inline]
+    #[target_feature(enable = "sse2")]
+    unsafe fn blend4(under: __m128i, weighted: [__m128; 3], sa: __m128) -> __m128i {
+        let [r, g, b, a] = channels(under);
+        let da = _mm_div_ps(a, _mm_set1_ps(255.0));
+        let keep = _mm_sub_ps(_mm_set1_ps(1.0), sa);
+        let oa = _mm_add_ps(sa, _mm_mul_ps(da, keep));
+        let blended = pack(over(r, weighted[0], da, keep, oa), over(g, weighted[1], da, keep, oa),
+                           over(b, weighted[2], da, keep, oa), round_ps(_mm_mul_ps(oa, _mm_set1_ps(255.0))));
+        //Pixels without any alpha left become transparent black
+        let empty = _mm_castps_si128(_mm_cmple_ps(oa, _mm_setzero_ps()));
+        _mm_andnot_si128(empty, blended)
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub unsafe fn fill(dst: &mut [u8], pixel: [u8; 4]) {
+        let value = _mm_set1_epi32(i32::from_ne_bytes(pixel));
+        let mut chunks = dst.chunks_exact_mut(CHUNK);
+        for chunk in &mut chunks {
+            _mm_storeu_si128(chunk.as_mut_ptr() as *mut __m128i, value);
+        }
+        for chunk in chunks.into_remainder().chunks_exact_mut(4) {
+            chunk.copy_from_slice(&pixel);
+        }
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub unsafe fn blend(dst: &mut [u8], src: &[u8; 4], coverage: f64) {
+        let sa = (src[3] as f32 / 255.0) * coverage.clamp(0.0, 1.0) as f32;
+        let weighted = [_mm_set1_ps(src[0] as f32 * sa), _mm_set1_ps(src[1] as f32 * sa), _mm_set1_ps(src[2] as f32 * sa)];
+        let mut chunks = dst.chunks_exact_mut(CHUNK);
+        for chunk in &mut chunks {
+            let under = _mm_loadu_si128(chunk.as_ptr() as *const __m128i);
+            _mm_storeu_si128(chunk.as_mut_ptr() as *mut __m128i, blend4(under, weighted, _mm_set1_ps(sa)));
+        }
+        super::reuse_runs(chunks.into_remainder(), |pixel| blend_bytes(pixel, src, coverage));
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub unsafe fn composite(dst: &mut [u8], src: &[u8], coverage: f64) {
+        let coverage32 = _mm_set1_ps(coverage.clamp(0.0, 1.0) as f32);
+        let len = dst.len().min(src.len()) / CHUNK * CHUNK;
+        let (dst_chunks, dst_rest) = dst.split_at_mut(len);
+        for (chunk, over) in dst_chunks.chunks_exact_mut(CHUNK).zip(src.chunks_exact(CHUNK)) {
+            let under = _mm_loadu_si128(chunk.as_ptr() as *const __m128i);
+            let top = _mm_loadu_si128(over.as_ptr() as *const __m128i);
+            let [r, g, b, a] = channels(top);
+            let sa = _mm_mul_ps(_mm_div_ps(a, _mm_set1_ps(255.0)), coverage32);
+            let blended = blend4(under, [_mm_mul_ps(r, sa), _mm_mul_ps(g, sa), _mm_mul_ps(b, sa)], sa);
+            //Pixels on top without alpha leave the pixels underneath untouched
+            let skip = _mm_cmpeq_epi32(_mm_srli_epi32(top, 24), _mm_setzero_si128());
+            _mm_storeu_si128(chunk.as_mut_ptr() as *mut __m128i, _mm_or_si128(_mm_and_si128(skip, under), _mm_andnot_si128(skip, blended)));
+        }
+        super::composite_scalar(dst_rest, &src[len..], &|pixel, over| blend_bytes(pixel, over, coverage));
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub unsafe fn gradient(dst: &mut [u8], from: &[u8; 4], to: &[u8; 4], t: (f64, f64)) {
+        let (t0, dt) = t;
+        //Color channels are mixed between 0..1 and scaled back, alpha is mixed as it is
+        let color = |v: u8| v as f64 / 255.0;
+        let from_channels = [color(from[0]), color(from[1]), color(from[2]), from[3] as f64];
+        let to_channels = [color(to[0]), color(to[1]), color(to[2]), to[3] as f64];
+        let mut chunks = dst.chunks_exact_mut(CHUNK);
+        let mut first = 0;
+        for chunk in &mut chunks {
+            //Two pixels per register of doubles, two registers per channel
+            let mut halves = [_mm_setzero_si128(); 8];
+            for half in 0..2 {
+                let i = (first + half * 2) as f64;
+                let index = _mm_setr_pd(i, i + 1.0);
+                let t = _mm_add_pd(_mm_set1_pd(t0), _mm_mul_pd(index, _mm_set1_pd(dt)));
+                let t = _mm_min_pd(_mm_max_pd(t, _mm_setzero_pd()), _mm_set1_pd(1.0));
+                let u = _mm_sub_pd(_mm_set1_pd(1.0), t);
+                for c in 0..4 {
+                    let mixed = _mm_add_pd(_mm_mul_pd(u, _mm_set1_pd(from_channels[c])), _mm_mul_pd(t, _mm_set1_pd(to_channels[c])));
+                    let scaled = if c < 3 { _mm_mul_pd(mixed, _mm_set1_pd(255.0)) } else { mixed };
+                    halves[c * 2 + half] = round_pd(scaled);
+                }
+            }
+            let pixels = pack(_mm_unpacklo_epi64(halves[0], halves[1]), _mm_unpacklo_epi64(halves[2], halves[3]),
+                              _mm_unpacklo_epi64(halves[4], halves[5]), _mm_unpacklo_epi64(halves[6], halves[7]));
+            _mm_storeu_si128(chunk.as_mut_ptr() as *mut __m128i, pixels);
+            first += 4;
+        }
+        super::gradient_scalar(chunks.into_remainder(), first, from, to, t);
+    }
+}
+
+/// Kernels holding a channel of eight pixels per register, evaluating the same operations in the same order
+/// as the scalar kernels, the pixels left over are handed to them
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod avx2 {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::*;
+    use graphic::canvas::blend_bytes;
+
+    /// Bytes of eight pixels
+    const CHUNK: usize = 32;
+
+    /// Returns the four channels of eight pixels as floats
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn channels(pixels: __m256i) -> [__m256; 4] {
+        let byte = _mm256_set1_epi32(0xFF);
+        [_mm256_cvtepi32_ps(_mm256_and_si256(pixels, byte)),
+         _mm256_cvtepi32_ps(_mm256_and_si256(_mm256_srli_epi32(pixels, 8), byte)),
+         _mm256_cvtepi32_ps(_mm256_and_si256(_mm256_srli_epi32(pixels, 16), byte)),
+         _mm256_cvtepi32_ps(_mm256_srli_epi32(pixels, 24))]
+    }
+
+    /// Rounds half away from zero like `f32::round`, clamps to 0..255 and returns the integers
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn round_ps(v: __m256) -> __m256i {
+        //Rounding to nearest would round halves to even, so the fraction is compared instead
+        let whole = _mm256_round_ps(v, _MM_FROUND_TO_ZERO | _MM_FROUND_NO_EXC);
+        let up = _mm256_cmp_ps(_mm256_sub_ps(v, whole), _mm256_set1_ps(0.5), _CMP_GE_OQ);
+        let rounded = _mm256_add_ps(whole, _mm256_and_ps(up, _mm256_set1_ps(1.0)));
+        _mm256_cvttps_epi32(_mm256_min_ps(_mm256_max_ps(rounded, _mm256_setzero_ps()), _mm256_set1_ps(255.0)))
+    }
+
+    /// Rounds half away from zero like `f64::round`, clamps to 0..255 and returns the integers
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn round_pd(v: __m256d) -> __m128i {
+        let whole = _mm256_round_pd(v, _MM_FROUND_TO_ZERO | _MM_FROUND_NO_EXC);
+        let up = _mm256_cmp_pd(_mm256_sub_pd(v, whole), _mm256_set1_pd(0.5), _CMP_GE_OQ);
+        let rounded = _mm256_add_pd(whole, _mm256_and_pd(up, _mm256_set1_pd(1.0)));
+        _mm256_cvttpd_epi32(_mm256_min_pd(_mm256_max_pd(rounded, _mm256_setzero_pd()), _mm256_set1_pd(255.0)))
+    }
+
+    /// Packs four channels of integers between 0..255 into pixels
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn pack(r: __m256i, g: __m256i, b: __m256i, a: __m256i) -> __m256i {
+        _mm256_or_si256(_mm256_or_si256(r, _mm256_slli_epi32(g, 8)), _mm256_or_si256(_mm256_slli_epi32(b, 16), _mm256_slli_epi32(a, 24)))
+    }
+
+    /// Returns a channel blended like `blend_bytes`, `weighted` is the channel on top multiplied with its alpha
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn over(under: __m256, weighted: __m256, da: __m256, keep: __m256, oa: __m256) -> __m256i {
+        round_ps(_mm256_div_ps(_mm256_add_ps(weighted, _mm256_mul_ps(_mm256_mul_ps(under, da), keep)), oa))
+    }
+
+    /// Blends eight pixels on top over eight pixels underneath
+    ///
+    /// # Arguments
+    ///
+    /// * `under` - Pixels underneath
+    /// * `weighted` - Color channels on top multiplied with `sa`
+    /// * `sa` - Alpha on top multiplied with the coverage
+    ///
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn blend8(under: __m256i, weighted: [__m256; 3], sa: __m256) -> __m256i {
+        let [r, g, b, a] = channels(under);
+        let da = _mm256_div_ps(a, _mm256_set1_ps(255.0));
+        let keep = _mm256_sub_ps(_mm256_set1_ps(1.0), sa);
+        let oa = _mm256_add_ps(sa, _mm256_mul_ps(da, keep));
+        let blended = pack(over(r, weighted[0], da, keep, oa), over(g, weighted[1], da, keep, oa),
+                           over(b, weighted[2], da, keep, oa), round_ps(_mm256_mul_ps(oa, _mm256_set1_ps(255.0))));
+        //Pixels without any alpha left become transparent black
+        let empty = _mm256_castps_si256(_mm256_cmp_ps(oa, _mm256_setzero_ps(), _CMP_LE_OQ));
+        _mm256_andnot_si256(empty, blended)
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn fill(dst: &mut [u8], pixel: [u8; 4]) {
+        let value = _mm256_set1_epi32(i32::from_ne_bytes(pixel));
+        let mut chunks = dst.chunks_exact_mut(CHUNK);
+        for chunk in &mut chunks {
+            _mm256_storeu_si256(chunk.as_mut_ptr() as *mut __m256i, value);
+        }
+        for chunk in chunks.into_remainder().chunks_exact_mut(4) {
+            chunk.copy_from_slice(&pixel);
+        }
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn blend(dst: &mut [u8], src: &[u8; 4], coverage: f64) {
+        let sa = (src[3] as f32 / 255.0) * coverage.clamp(0.0, 1.0) as f32;
+        let weighted = [_mm256_set1_ps(src[0] as f32 * sa), _mm256_set1_ps(src[1] as f32 * sa), _mm256_set1_ps(src[2] as f32 * sa)];
+        let mut chunks = dst.chunks_exact_mut(CHUNK);
+        for chunk in &mut chunks {
+            let under = _mm256_loadu_si256(chunk.as_ptr() as *const __m256i);
+            _mm256_storeu_si256(chunk.as_mut_ptr() as *mut __m256i, blend8(under, weighted, _mm256_set1_ps(sa)));
+        }
+        super::reuse_runs(chunks.into_remainder(), |pixel| blend_bytes(pixel, src, coverage));
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn composite(dst: &mut [u8], src: &[u8], coverage: f64) {
+        let coverage32 = _mm256_set1_ps(coverage.clamp(0.0, 1.0) as f32);
+        let len = dst.len().min(src.len()) / CHUNK * CHUNK;
+        let (dst_chunks, dst_rest) = dst.split_at_mut(len);
+        for (chunk, over) in dst_chunks.chunks_exact_mut(CHUNK).zip(src.chunks_exact(CHUNK)) {
+            let under = _mm256_loadu_si256(chunk.as_ptr() as *const __m256i);
+            let top = _mm256_loadu_si256(over.as_ptr() as *const __m256i);
+            let [r, g, b, a] = channels(top);
+            let sa = _mm256_mul_ps(_mm256_div_ps(a, _mm256_set1_ps(255.0)), coverage32);
+            let blended = blend8(under, [_mm256_mul_ps(r, sa), _mm256_mul_ps(g, sa), _mm256_mul_ps(b, sa)], sa);
+            //Pixels on top without alpha leave the pixels underneath untouched
+            let skip = _mm256_cmpeq_epi32(_mm256_srli_epi32(top, 24), _mm256_setzero_si256());
+            _mm256_storeu_si256(chunk.as_mut_ptr() as *mut __m256i, _mm256_blendv_epi8(blended, under, skip));
+        }
+        super::composite_scalar(dst_rest, &src[len..], &|pixel, over| blend_bytes(pixel, over, coverage));
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn gradient(dst: &mut [u8], from: &[u8; 4], to: &[u8; 4], t: (f64, f64)) {
+        let (t0, dt) = t;
+        //Color channels are mixed between 0..1 and scaled back, alpha is mixed as it is
+        let color = |v: u8| v as f64 / 255.0;
+        let from_channels = [color(from[0]), color(from[1]), color(from[2]), from[3] as f64];
+        let to_channels = [color(to[0]), color(to[1]), color(to[2]), to[3] as f64];
+        let mut chunks = dst.chunks_exact_mut(CHUNK);
+        let mut first = 0;
+        for chunk in &mut chunks {
+            //Four pixels per register of doubles, two registers per channel
+            let mut halves = [_mm_setzero_si128(); 8];
+            for half in 0..2 {
+                let i = (first + half * 4) as f64;
+                let index = _mm256_setr_pd(i, i + 1.0, i + 2.0, i + 3.0);
+                let t = _mm256_add_pd(_mm256_set1_pd(t0), _mm256_mul_pd(index, _mm256_set1_pd(dt)));
+                let t = _mm256_min_pd(_mm256_max_pd(t, _mm256_setzero_pd()), _mm256_set1_pd(1.0));
+                let u = _mm256_sub_pd(_mm256_set1_pd(1.0), t);
+                for c in 0..4 {
+                    let mixed = _mm256_add_pd(_mm256_mul_pd(u, _mm256_set1_pd(from_channels[c])), _mm256_mul_pd(t, _mm256_set1_pd(to_channels[c])));
+                    let scaled = if c < 3 { _mm256_mul_pd(mixed, _mm256_set1_pd(255.0)) } else { mixed };
+                    halves[c * 2 + half] = round_pd(scaled);
+                }
+            }
+            let pixels = pack(_mm256_set_m128i(halves[1], halves[0]), _mm256_set_m128i(halves[3], halves[2]),
+                              _mm256_set_m128i(halves[5], halves[4]), _mm256_set_m128i(halves[7], halves[6]));
+            _mm256_storeu_si256(chunk.as_mut_ptr() as *mut __m256i, pixels);
+            first += 8;
+        }
+        super::gradient_scalar(chunks.into_remainder(), first, from, to, t);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use raster::Color;
+    use graphic::{ColorSpace, interpolate_color};
+
+    /// Returns reproducible pseudo random bytes
+    fn noise(len: usize, seed: u32) -> Vec<u8> {
+        let mut state = seed;
+        (0..len).map(|_| {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (state >> 24) as u8
+        }).collect()
+    }
+
+    #[test]
+    fn kernels_match_the_scalar_ones() {
+        let mut under = noise(4 * 1027, 1);
+        let mut over = noise(4 * 1027, 2);
+        //Transparent pixels underneath and on top, in whole registers and in the pixels left over
+        for pixel in (0..1027).filter(|i| i % 5 == 0 || (16..24).contains(i)) {
+            under[pixel * 4 + 3] = 0;
+            over[pixel * 4 + 3] = 0;
+        }
+        for isa in Isa::supported() {
+            for (i, coverage) in [0.0, 0.3, 0.5, 0.999, 1.0].iter().enumerate() {
+                let src = [over[i], over[i + 1], over[i + 2], over[i + 3]];
+                let (mut scalar, mut vector) = (under.clone(), under.clone());
+                blend(Isa::Scalar, &mut scalar, &src, *coverage, false);
+                blend(isa, &mut vector, &src, *coverage, false);
+                assert_eq!(scalar, vector, "{}", isa.name());
+                let (mut scalar, mut vector) = (under.clone(), under.clone());
+                composite(Isa::Scalar, &mut scalar, &over, *coverage, false);
+                composite(isa, &mut vector, &over, *coverage, false);
+                assert_eq!(scalar, vector, "{}", isa.name());
+            }
+            let (mut scalar, mut vector) = (under.clone(), under.clone());
+            fill(Isa::Scalar, &mut scalar, [1, 2, 3, 4]);
+            fill(isa, &mut vector, [1, 2, 3, 4]);
+            assert_eq!(scalar, vector, "{}", isa.name());
+            gradient(Isa::Scalar, &mut scalar, &[255, 0, 17, 255], &[3, 250, 128, 0], (-0.1, 0.01));
+            gradient(isa, &mut vector, &[255, 0, 17, 255], &[3, 250, 128, 0], (-0.1, 0.01));
+            assert_eq!(scalar, vector, "{}", isa.name());
+        }
+    }
+
+    #[test]
+    fn gradient_matches_interpolate_color() {
+        let (from, to) = (Color::rgba(255, 0, 17, 255), Color::rgba(3, 250, 128, 0));
+        let mut row = vec![0; 4 * 101];
+        gradient(Isa::detect(), &mut row, &[from.r, from.g, from.b, from.a], &[to.r, to.g, to.b, to.a], (0.0, 0.01));
+        for (i, pixel) in row.chunks(4).enumerate() {
+            let color = interpolate_color(&from, &to, (i as f64 * 0.01).clamp(0.0, 1.0), ColorSpace::Srgb);
+            assert_eq!([color.r, color.g, color.b, color.a], pixel);
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use geometric::BoundingBox;
+use graphic::ShapeId;
+
+/// Maximum number of items in a leaf of the hierarchy
+const LEAF_SIZE: usize = 8;
+
+/// Shape handle with its bounds, `None` once the shape was removed
+struct Item {
+    id: ShapeId,
+    bounds: Option<BoundingBox>,
+    /// Leaf holding the item
+    leaf: usize
+}
+
+/// Where the bounds of a shape are kept
+#[derive(Clone, Copy)]
+enum Slot {
+    /// Position in the items of the hierarchy
+    Tree(usize),
+    /// Position in the shapes added since the hierarchy was built
+    Added(usize)
+}
+
+enum Kind {
+    /// Refers to a range of items
+    Leaf { start: usize, end: usize },
+    Branch { left: usize, right: usize }
+}
+
+/// Node of the hierarchy, `bounds` is `None` if all items below were removed
+struct Node {
+    bounds: Option<BoundingBox>,
+    parent: Option<usize>,
+    kind: Kind
+}
+
+/// Bounding volume hierarchy over the bounding boxes of the shapes of a Renderer
+///
+/// Changed bounds are refitted into the hierarchy, added shapes are kept in a list beside it until
+/// added and removed shapes make up half of the hierarchy and it is built anew.
+pub struct SpatialIndex {
+    items: Vec<Item>,
+    nodes: Vec<Node>,
+    added: Vec<(ShapeId, BoundingBox)>,
+    slots: HashMap<ShapeId, Slot>,
+    /// Number of removed items still held by the hierarchy
+    removed: usize
+}
+
+impl SpatialIndex {
+    /// Builds the hierarchy over the bounds of shapes
+    pub fn new<I: IntoIterator<Item = (ShapeId, BoundingBox)>>(shapes: I) -> SpatialIndex {
+        let mut index = SpatialIndex {
+            items: shapes.into_iter().map(|(id, bounds)| Item { id, bounds: Some(bounds), leaf: 0 }).collect(),
+            nodes: Vec::new(),
+            added: Vec::new(),
+            slots: HashMap::new(),
+            removed: 0
+        };
+        if !index.items.is_empty() {
+            let len = index.items.len();
+            index.build(0, len, None);
+        }
+        for (position, item) in index.items.iter().enumerate() {
+            index.slots.insert(item.id, Slot::Tree(position));
+        }
+        index
+    }
+
+    /// Builds the node over `items[start..end]` and returns its position
+    fn build(&mut self, start: usize, end: usize, parent: Option<usize>) -> usize {
+        let bounds = BoundingBox::union_all(self.items[start..end].iter().filter_map(|item| item.bounds));
+        let position = self.nodes.len();
+        self.nodes.push(Node { bounds, parent, kind: Kind::Leaf { start, end } });
+        if end - start <= LEAF_SIZE {
+            for item in &mut self.items[start..end] {
+                item.leaf = position;
+            }
+            return position;
+        }
+        //Splits at the median center along the longer axis
+        let center = |item: &Item| item.bounds.unwrap().center();
+        let centers = BoundingBox::from_points(self.items[start..end].iter().map(center)).unwrap();
+        let horizontal = centers.width() >= centers.height();
+        let key = |item: &Item| if horizontal { center(item).0 } else { center(item).1 };
+        let middle = (end - start) / 2;
+        self.items[start..end].select_nth_unstable_by(middle, |a, b| key(a).partial_cmp(&key(b)).unwrap_or(Ordering::Equal));
+        let left = self.build(start, start + middle, Some(position));
+        let right = self.build(start + middle, end, Some(position));
+        self.nodes[position].kind = Kind::Branch { left, right };
+        position
+    }
+
+    /// Builds the hierarchy anew over all shapes
+    fn rebuild(&mut self) {
+        let shapes: Vec<(ShapeId, BoundingBox)> = self.items.iter()
+            .filter_map(|item| item.bounds.map(|bounds| (item.id, bounds)))
+            .chain(self.added.drain(..))
+            .collect();
+        *self = SpatialIndex::new(shapes);
+    }
+
+    /// Rebuilds once the shapes outside of the hierarchy or removed from it make up half of it
+    fn rebuild_if_degraded(&mut self) {
+        if self.added.len() + self.removed > self.items.len() / 2 + LEAF_SIZE {
+            self.rebuild();
+        }
+    }
+
+    /// Adds the bounds of a shape, replacing the bounds it had before
+    pub fn insert(&mut self, id: ShapeId, bounds: BoundingBox) {
+        match self.slots.get(&id).cloned() {
+            Some(Slot::Tree(position)) => {
+                self.items[position].bounds = Some(bounds);
+                let leaf = self.items[position].leaf;
+                self.refit(leaf);
+            }
+            Some(Slot::Added(position)) => self.added[position].1 = bounds,
+            None => {
+                self.slots.insert(id, Slot::Added(self.added.len()));
+                self.added.push((id, bounds));
+                self.rebuild_if_degraded();
+            }
+        }
+    }
+
+    /// Removes the bounds of a shape
+    pub fn remove(&mut self, id: ShapeId) {
+        match self.slots.remove(&id) {
+            Some(Slot::Tree(position)) => {
+                self.items[position].bounds = None;
+                self.removed += 1;
+                let leaf = self.items[position].leaf;
+                self.refit(leaf);
+            }
+            Some(Slot::Added(position)) => {
+                self.added.swap_remove(position);
+                if let Some(&(moved, _)) = self.added.get(position) {
+                    self.slots.insert(moved, Slot::Added(position));
+                }
+            }
+            None => return
+        }
+        self.rebuild_if_degraded();
+    }
+
+    /// Recomputes the bounds of a node and its ancestors after the bounds of an item changed
+    fn refit(&mut self, node: usize) {
+        let mut node = Some(node);
+        while let Some(position) = node {
+            let bounds = match self.nodes[position].kind {
+                Kind::Leaf { start, end } => BoundingBox::union_all(self.items[start..end].iter().filter_map(|item| item.bounds)),
+                Kind::Branch { left, right } => {
+                    BoundingBox::union_all(self.nodes[left].bounds.into_iter().chain(self.nodes[right].bounds))
+                }
+            };
+            self.nodes[position].bounds = bounds;
+            node = self.nodes[position].parent;
+        }
+    }
+
+    /// Returns the shapes whose bounds intersect a rectangle, in no particular order
+    pub fn query(&self, rect: &BoundingBox) -> Vec<ShapeId> {
+        let mut hits: Vec<ShapeId> = self.added.iter().filter(|&&(_, bounds)| bounds.intersects(rect)).map(|&(id, _)| id).collect();
+        let mut stack = if self.nodes.is_empty() { vec![] } else { vec![0] };
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            if !node.bounds.is_some_and(|bounds| bounds.intersects(rect)) {
+                continue;
+            }
+            match node.kind {
+                Kind::Leaf { start, end } => {
+                    hits.extend(self.items[start..end].iter()
+                        .filter(|item| item.bounds.is_some_and(|bounds| bounds.intersects(rect)))
+                        .map(|item| item.id));
+                }
+                Kind::Branch { left, right } => {
+                    stack.push(left);
+                    stack.push(right);
+                }
+            }
+        }
+        hits
+    }
+
+    /// Returns the shape with the smallest distance to a coordinate
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - X-Coordinate to measure from
+    /// * `y` - Y-Coordinate to measure from
+    /// * `distance` - Exact distance of a shape, never smaller than the distance to its bounds, infinite to skip it
+    ///
+    pub fn nearest(&self, x: f64, y: f64, distance: &dyn Fn(ShapeId) -> f64) -> Option<(ShapeId, f64)> {
+        let mut best: Option<(ShapeId, f64)> = None;
+        for &(id, bounds) in &self.added {
+            SpatialIndex::closest(id, &bounds, x, y, distance, &mut best);
+        }
+        if !self.nodes.is_empty() {
+            self.nearest_in(0, x, y, distance, &mut best);
+        }
+        best
+    }
+
+    /// Keeps a shape as the closest one if it is closer than the best so far
+    fn closest(id: ShapeId, bounds: &BoundingBox, x: f64, y: f64, distance: &dyn Fn(ShapeId) -> f64, best: &mut Option<(ShapeId, f64)>) {
+        let closer = |d: f64, best: &Option<(ShapeId, f64)>| best.is_none_or(|(_, b)| d < b);
+        if !closer(bounds.distance(x, y), best) {
+            return;
+        }
+        let d = distance(id);
+        if d.is_finite() && closer(d, best) {
+            *best = Some((id, d));
+        }
+    }
+
+    fn nearest_in(&self, node: usize, x: f64, y: f64, distance: &dyn Fn(ShapeId) -> f64, best: &mut Option<(ShapeId, f64)>) {
+        let closer = |d: f64, best: &Option<(ShapeId, f64)>| best.is_none_or(|(_, b)| d < b);
+        let bounds_distance = |node: usize| self.nodes[node].bounds.map_or(f64::INFINITY, |bounds| bounds.distance(x, y));
+        match self.nodes[node].kind {
+            Kind::Leaf { start, end } => {
+                for item in &self.items[start..end] {
+                    if let Some(ref bounds) = item.bounds {
+                        SpatialIndex::closest(item.id, bounds, x, y, distance, best);
+                    }
+                }
+            }
+            Kind::Branch { left, right } => {
+                let dl = bounds_distance(left);
+                let dr = bounds_distance(right);
+                let (first, d_first, second, d_second) = if dl <= dr { (left, dl, right, dr) } else { (right, dr, left, dl) };
+                if closer(d_first, best) {
+                    self.nearest_in(first, x, y, distance, best);
+                }
+                if closer(d_second, best) {
+                    self.nearest_in(second, x, y, distance, best);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell(i: u64) -> BoundingBox {
+        let (x, y) = ((i % 10) as f64 * 10.0, (i / 10) as f64 * 10.0);
+        BoundingBox::new(x, y, x + 5.0, y + 5.0)
+    }
+
+    fn grid() -> SpatialIndex {
+        SpatialIndex::new((0..100).map(|i| (ShapeId(i), cell(i))))
+    }
+
+    fn sorted(mut ids: Vec<ShapeId>) -> Vec<ShapeId> {
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn query_matches_linear_scan() {
+        let rect = BoundingBox::new(12.0, 3.0, 31.0, 14.0);
+        let expected: Vec<ShapeId> = (0..100).filter(|&i| cell(i).intersects(&rect)).map(ShapeId).collect();
+        assert_eq!(6, expected.len());
+        assert_eq!(expected, sorted(grid().query(&rect)));
+    }
+
+    #[test]
+    fn nearest_by_bounds() {
+        let nearest = grid().nearest(47.0, 52.0, &|id| cell(id.0).distance(47.0, 52.0));
+        assert_eq!(Some((ShapeId(54), 2.0)), nearest);
+    }
+
+    #[test]
+    fn follows_changes_without_rebuilding() {
+        let mut index = grid();
+        let rect = BoundingBox::new(200.0, 200.0, 210.0, 210.0);
+        //Moves a shape far out, the hierarchy grows to include it
+        index.insert(ShapeId(3), BoundingBox::new(202.0, 202.0, 204.0, 204.0));
+        index.insert(ShapeId(100), BoundingBox::new(205.0, 205.0, 206.0, 206.0));
+        index.remove(ShapeId(4));
+        assert_eq!(100, index.items.len());
+        assert_eq!(vec![ShapeId(3), ShapeId(100)], sorted(index.query(&rect)));
+        assert!(!index.query(&cell(4)).contains(&ShapeId(4)));
+        assert_eq!(Some(ShapeId(3)), index.nearest(201.0, 201.0, &|id| if id.0 == 3 { 1.0 } else { 2.0 }).map(|(id, _)| id));
+        index.remove(ShapeId(3));
+        assert_eq!(vec![ShapeId(100)], index.query(&rect));
+    }
+
+    #[test]
+    fn rebuilds_once_half_of_the_shapes_changed() {
+        let mut index = grid();
+        for i in 100..160 {
+            index.insert(ShapeId(i), cell(i - 100));
+        }
+        assert!(index.added.len() < 60);
+        let expected: Vec<ShapeId> = (0..100).chain(100..160).filter(|&i| cell(i % 100).intersects(&cell(55))).map(ShapeId).collect();
+        assert_eq!(expected, sorted(index.query(&cell(55))));
+    }
+}
@@ -0,0 +1,234 @@
+extern crate raster;
+
+use std::sync::Mutex;
+use rayon::prelude::*;
+use raster::Color;
+use geometric::Geometric2D;
+use graphic::{Canvas, BlendMode, DepthBuffer, Framebuffer, HdrBuffer};
+use graphic::layer::{self, LayerBuffer};
+
+/// Width and height of a tile in pixels
+const TILE_SIZE: i32 = 64;
+
+/// Pixel buffer a Renderer can split into tiles
+pub(crate) trait TileBuffer: LayerBuffer + Send + Sync {
+    /// Overwrites the pixels at `x, y` with a rectangle `(x0, y0, x1, y1)` of another buffer without blending or conversion
+    ///
+    /// The rectangle has to fit into both buffers.
+    fn copy_rect(&mut self, x: i32, y: i32, src: &Self, rect: (i32, i32, i32, i32));
+}
+
+impl TileBuffer for Framebuffer {
+    fn copy_rect(&mut self, x: i32, y: i32, src: &Framebuffer, rect: (i32, i32, i32, i32)) {
+        let (x0, y0, x1, y1) = rect;
+        let len = (x1 - x0).max(0) as usize * 4;
+        let (stride, src_stride) = (self.stride(), src.stride());
+        for row in 0..y1 - y0 {
+            let start = (y + row) as usize * stride + x as usize * 4;
+            let src_start = (y0 + row) as usize * src_stride + x0 as usize * 4;
+            self.as_bytes_mut()[start..start + len].copy_from_slice(&src.as_bytes()[src_start..src_start + len]);
+        }
+    }
+}
+
+impl TileBuffer for HdrBuffer {
+    fn copy_rect(&mut self, x: i32, y: i32, src: &HdrBuffer, rect: (i32, i32, i32, i32)) {
+        HdrBuffer::copy_rect(self, x, y, src, rect);
+    }
+}
+
+/// Part of a larger Canvas, pixels outside of the tile are ignored
+struct Tile<B> {
+    buffer: B,
+    depth: Option<DepthBuffer>,
+    x0: i32,
+    y0: i32,
+    width: i32,
+    height: i32
+}
+
+impl<B: LayerBuffer> Canvas for Tile<B> {
+    fn width(&self) -> i32 {
+        self.width
+    }
+
+    fn height(&self) -> i32 {
+        self.height
+    }
+
+    fn get_pixel(&self, x: i32, y: i32) -> Option<Color> {
+        self.buffer.get_pixel(x - self.x0, y - self.y0)
+    }
+
+    fn set_pixel(&mut self, x: i32, y: i32, color: &Color) {
+        self.buffer.set_pixel(x - self.x0, y - self.y0, color);
+    }
+
+    fn blend_pixel(&mut self, x: i32, y: i32, color: &Color, coverage: f64) {
+        self.buffer.blend_pixel(x - self.x0, y - self.y0, color, coverage);
+    }
+
+    fn linear_blending(&self) -> bool {
+        self.buffer.linear_blending()
+    }
+
+    fn clip_rect(&self) -> (i32, i32, i32, i32) {
+        (self.x0, self.y0, self.x0 + self.buffer.width(), self.y0 + self.buffer.height())
+    }
+
+    fn depth_test(&mut self, x: i32, y: i32, depth: f64) -> bool {
+        match self.depth {
+            Some(ref mut buffer) => buffer.test(x - self.x0, y - self.y0, depth),
+            None => true
+        }
+    }
+
+    fn fill_span(&mut self, x0: i32, x1: i32, y: i32, color: &Color) {
+        self.buffer.fill_span(x0 - self.x0, x1 - self.x0, y - self.y0, color);
+    }
+
+    fn fill_gradient_span(&mut self, x0: i32, x1: i32, y: i32, from: &Color, to: &Color, t: (f64, f64)) {
+        self.buffer.fill_gradient_span(x0 - self.x0, x1 - self.x0, y - self.y0, from, to, t);
+    }
+
+    fn blend_span(&mut self, x0: i32, x1: i32, y: i32, color: &Color, coverage: f64) {
+        self.buffer.blend_span(x0 - self.x0, x1 - self.x0, y - self.y0, color, coverage);
+    }
+
+    fn draw_layer(&mut self, opacity: f64, draw: &mut dyn FnMut(&mut dyn Canvas)) {
+        //The layer only covers the tile
+        let layer = self.buffer.blank(self.buffer.width(), self.buffer.height());
+        let (x0, y0) = (self.x0, self.y0);
+        let layer = layer::draw_offscreen(self as &mut dyn Canvas, layer, x0, y0, draw);
+        self.buffer.composite_layer(&layer, 0, 0, opacity, BlendMode::Normal);
+    }
+}
+
+/// Draws Geometric-Objects tile by tile on the rayon thread pool
+///
+/// Every tile draws the Geometric-Objects overlapping it in the given order,
+/// so the pixels are the same as when drawing them one after another.
+/// A tile copies its pixels from `target` and writes them back as soon as it is drawn.
+///
+/// # Arguments
+///
+/// * `target` - Buffer to draw into
+/// * `shapes` - Geometric-Objects in drawing order
+/// * `depth` - DepthBuffer of the size of `target` to test against
+/// * `draw` - Draws a Geometric-Object onto a Canvas
+///
+pub(crate) fn draw_tiled<B: TileBuffer>(target: &mut B, shapes: &[&dyn Geometric2D], depth: Option<&mut DepthBuffer>,
+                                        draw: &(dyn Fn(&dyn Geometric2D, &mut dyn Canvas) + Sync)) {
+    let (width, height) = (target.width(), target.height());
+    let columns = (width + TILE_SIZE - 1) / TILE_SIZE;
+    let rows = (height + TILE_SIZE - 1) / TILE_SIZE;
+    if columns <= 0 || rows <= 0 {
+        return;
+    }
+    let mut bins = vec![Vec::new(); (columns * rows) as usize];
+    for (i, geo) in shapes.iter().enumerate() {
+        //Anti-aliasing may paint a pixel beyond the bounds
+        let bounds = match geo.bounding_box() {
+            Some(bounds) => bounds.expand(2.0),
+            None => continue
+        };
+        if bounds.max_x < 0.0 || bounds.max_y < 0.0 || bounds.min_x >= width as f64 || bounds.min_y >= height as f64 {
+            continue;
+        }
+        let tile = |v: f64, count: i32| ((v / TILE_SIZE as f64).floor().max(0.0) as i32).min(count - 1);
+        for row in tile(bounds.min_y, rows)..=tile(bounds.max_y, rows) {
+            for column in tile(bounds.min_x, columns)..=tile(bounds.max_x, columns) {
+                bins[(row * columns + column) as usize].push(i);
+            }
+        }
+    }
+    //Tiles don't overlap, the lock only keeps the copies from racing each other
+    let shared = Mutex::new((target, depth));
+    bins.par_iter().enumerate().for_each(|(i, bin)| {
+        if bin.is_empty() {
+            return;
+        }
+        let x0 = (i as i32 % columns) * TILE_SIZE;
+        let y0 = (i as i32 / columns) * TILE_SIZE;
+        let tile_width = TILE_SIZE.min(width - x0);
+        let tile_height = TILE_SIZE.min(height - y0);
+        let rect = (x0, y0, x0 + tile_width, y0 + tile_height);
+        let mut tile = {
+            let shared = shared.lock().unwrap();
+            let (ref source, ref depth_source) = *shared;
+            let mut buffer = source.blank(tile_width, tile_height);
+            buffer.copy_rect(0, 0, &**source, rect);
+            let depth = depth_source.as_ref().map(|depth_source| {
+                let mut depth = DepthBuffer::new(tile_width, tile_height);
+                depth.copy_rect(0, 0, depth_source, rect);
+                depth
+            });
+            Tile { buffer, depth, x0, y0, width, height }
+        };
+        for &geo in bin {
+            draw(shapes[geo], &mut tile);
+        }
+        let tile_rect = (0, 0, tile_width, tile_height);
+        let mut shared = shared.lock().unwrap();
+        let (ref mut target, ref mut depth) = *shared;
+        target.copy_rect(x0, y0, &tile.buffer, tile_rect);
+        if let (Some(depth), Some(tile_depth)) = (depth.as_mut(), tile.depth.as_ref()) {
+            depth.copy_rect(x0, y0, tile_depth, tile_rect);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use geometric::{Circle2D, Group, Line2D, Point2D, Triangle2D};
+
+    fn translucent_group() -> Group {
+        let mut group = Group::new();
+        group.add(Circle2D::new(40.0, Point2D::new_color(60.0, 140.0, Color::green())));
+        group.add(Circle2D::new(40.0, Point2D::new_color(90.0, 140.0, Color::blue())));
+        group.set_opacity(0.5);
+        group
+    }
+
+    #[test]
+    fn matches_serial_drawing() {
+        let shapes: Vec<Box<dyn Geometric2D>> = vec![
+            Box::new(Triangle2D::new(Point2D::new_color(3.0, 4.0, Color::red()),
+                                     Point2D::new_color(150.0, 20.0, Color::green()),
+                                     Point2D::new_color(40.0, 190.0, Color::blue()))),
+            Box::new(Circle2D::new(50.0, Point2D::new_color(100.0, 100.0, Color::rgba(255, 255, 0, 128)))),
+            Box::new(Line2D::new(Point2D::new_color(0.0, 199.0, Color::white()), Point2D::new_color(199.0, 0.0, Color::red()))),
+            Box::new(translucent_group())
+        ];
+        let shapes: Vec<&dyn Geometric2D> = shapes.iter().map(|geo| &**geo).collect();
+        let draw = |geo: &dyn Geometric2D, canvas: &mut dyn Canvas| {
+            geo.draw(canvas);
+            geo.draw_outline_aa(canvas);
+        };
+        let mut serial = Framebuffer::new(200, 200);
+        for geo in &shapes {
+            draw(*geo, &mut serial);
+        }
+        let mut tiled = Framebuffer::new(200, 200);
+        draw_tiled(&mut tiled, &shapes, None, &draw);
+        assert!(serial.as_bytes() == tiled.as_bytes());
+    }
+
+    #[test]
+    fn skips_shapes_outside_of_the_target() {
+        let shapes: Vec<Box<dyn Geometric2D>> = vec![
+            Box::new(Circle2D::new(10.0, Point2D::new(240.0, 100.0))),
+            Box::new(Circle2D::new(10.0, Point2D::new(100.0, 215.0))),
+            Box::new(Circle2D::new(10.0, Point2D::new(-15.0, 100.0))),
+            Box::new(Circle2D::new(5.0, Point2D::new(150.0, 150.0)))
+        ];
+        let shapes: Vec<&dyn Geometric2D> = shapes.iter().map(|geo| &**geo).collect();
+        let drawn = AtomicUsize::new(0);
+        draw_tiled(&mut Framebuffer::new(200, 200), &shapes, None, &|_, _| {
+            drawn.fetch_add(1, Ordering::SeqCst);
+        });
+        assert_eq!(1, drawn.load(Ordering::SeqCst));
+    }
+}
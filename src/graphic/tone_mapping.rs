@@ -0,0 +1,62 @@
+/// Operator compressing linear light values above 1.0 into the displayable range 0..1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToneMapping {
+    /// Cuts off everything above 1.0
+    Clamp,
+    /// `v / (1 + v)`, never saturates
+    Reinhard,
+    /// Filmic curve of the Academy Color Encoding System, fitted by Krzysztof Narkowicz
+    AcesFilmic
+}
+
+impl ToneMapping {
+    /// Maps a linear light channel, already multiplied by the exposure, to 0..1
+    ///
+    /// # Arguments
+    ///
+    /// * `v` - Linear light channel
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate graphic_library;
+    /// use graphic_library::graphic::ToneMapping;
+    /// fn main() {
+    /// //returns 0.75
+    /// let mapped = ToneMapping::Reinhard.map(3.0);
+    /// }
+    /// ```
+    pub fn map(self, v: f32) -> f32 {
+        let v = v.max(0.0);
+        match self {
+            ToneMapping::Clamp => v.min(1.0),
+            ToneMapping::Reinhard => v / (1.0 + v),
+            ToneMapping::AcesFilmic => ((v * (2.51 * v + 0.03)) / (v * (2.43 * v + 0.59) + 0.14)).clamp(0.0, 1.0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operators_stay_in_range() {
+        for op in &[ToneMapping::Clamp, ToneMapping::Reinhard, ToneMapping::AcesFilmic] {
+            assert_eq!(0.0, op.map(0.0));
+            assert_eq!(0.0, op.map(-1.0));
+            let mut last = 0.0;
+            for i in 1..100 {
+                let v = op.map(i as f32 * 0.5);
+                assert!(v >= last && v <= 1.0);
+                last = v;
+            }
+        }
+    }
+
+    #[test]
+    fn reinhard() {
+        assert_eq!(0.5, ToneMapping::Reinhard.map(1.0));
+        assert_eq!(1.0, ToneMapping::Clamp.map(7.0));
+    }
+}
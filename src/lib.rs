@@ -1,4 +1,6 @@
 extern crate raster;
+#[cfg(feature = "parallel")]
+extern crate rayon;
 
 pub mod graphic;
 pub mod geometric;
\ No newline at end of file
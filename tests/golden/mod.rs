@@ -0,0 +1,201 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use raster::Image;
+use graphic_library::graphic::Renderer;
+
+/// How far a rendered image may deviate from its reference
+#[derive(Debug, Clone, Copy)]
+pub struct Tolerance {
+    /// Largest difference of a channel between 0..255 still counting as equal
+    pub channel: u8,
+    /// Share of pixels between 0..1 allowed to differ by more than `channel`
+    pub pixels: f64
+}
+
+impl Tolerance {
+    /// Returns a Tolerance accepting only identical images
+    pub fn exact() -> Tolerance {
+        Tolerance { channel: 0, pixels: 0.0 }
+    }
+}
+
+impl Default for Tolerance {
+    /// Allows rounding differences of one step per channel, e.g. from another libm
+    fn default() -> Tolerance {
+        Tolerance { channel: 1, pixels: 0.0 }
+    }
+}
+
+/// Differences between a rendered image and its reference
+#[derive(Debug, Clone, Copy)]
+pub struct Comparison {
+    /// Largest difference of a channel between 0..255
+    pub max_difference: u8,
+    /// Number of pixels differing by more than the tolerated difference of a channel
+    pub mismatched: usize,
+    /// Peak signal-to-noise ratio over all channels in dB, infinite for identical images
+    pub psnr: f64,
+    /// Mean structural similarity of the luma between -1..1, 1 for identical images
+    pub ssim: f64
+}
+
+/// Compares two images of the same size channel by channel
+///
+/// # Arguments
+///
+/// * `expected` - Reference image
+/// * `actual` - Rendered image
+/// * `tolerance` - Largest difference of a channel still counting as equal
+///
+pub fn compare(expected: &Image, actual: &Image, tolerance: u8) -> Comparison {
+    assert_eq!((expected.width, expected.height), (actual.width, actual.height), "The images differ in size");
+    let mut max_difference = 0;
+    let mut mismatched = 0;
+    let mut squared = 0.0;
+    for (e, a) in expected.bytes.chunks(4).zip(actual.bytes.chunks(4)) {
+        let difference = e.iter().zip(a).map(|(e, a)| (*e as i32 - *a as i32).unsigned_abs() as u8).max().unwrap();
+        max_difference = max_difference.max(difference);
+        if difference > tolerance {
+            mismatched += 1;
+        }
+        squared += e.iter().zip(a).map(|(e, a)| (*e as f64 - *a as f64).powi(2)).sum::<f64>();
+    }
+    let mse = squared / expected.bytes.len().max(1) as f64;
+    let psnr = if mse == 0.0 { f64::INFINITY } else { 10.0 * (255.0 * 255.0 / mse).log10() };
+    Comparison { max_difference, mismatched, psnr, ssim: ssim(expected, actual) }
+}
+
+/// Returns the luma of every pixel, composited over black
+fn luma(image: &Image) -> Vec<f64> {
+    image.bytes.chunks(4)
+        .map(|p| (0.299 * p[0] as f64 + 0.587 * p[1] as f64 + 0.114 * p[2] as f64) * p[3] as f64 / 255.0)
+        .collect()
+}
+
+/// Returns the mean structural similarity of the luma over 8x8 windows, overlapping by half a window
+fn ssim(expected: &Image, actual: &Image) -> f64 {
+    const WINDOW: i32 = 8;
+    const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+    const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+    let (x, y) = (luma(expected), luma(actual));
+    let width = expected.width;
+    //Images smaller than a window are compared as a whole
+    let (window_width, window_height) = (WINDOW.min(width), WINDOW.min(expected.height));
+    let starts = |size: i32, window: i32| (0..=size - window).step_by((window as usize / 2).max(1)).collect::<Vec<i32>>();
+    let mut sum = 0.0;
+    let mut count = 0;
+    for &y0 in &starts(expected.height, window_height) {
+        for &x0 in &starts(width, window_width) {
+            let indices: Vec<usize> = (y0..y0 + window_height)
+                .flat_map(|y| (x0..x0 + window_width).map(move |x| (y * width + x) as usize))
+                .collect();
+            let n = indices.len() as f64;
+            let mean_x = indices.iter().map(|&i| x[i]).sum::<f64>() / n;
+            let mean_y = indices.iter().map(|&i| y[i]).sum::<f64>() / n;
+            let (mut var_x, mut var_y, mut covariance) = (0.0, 0.0, 0.0);
+            for &i in &indices {
+                var_x += (x[i] - mean_x) * (x[i] - mean_x);
+                var_y += (y[i] - mean_y) * (y[i] - mean_y);
+                covariance += (x[i] - mean_x) * (y[i] - mean_y);
+            }
+            let (var_x, var_y, covariance) = (var_x / n, var_y / n, covariance / n);
+            sum += ((2.0 * mean_x * mean_y + C1) * (2.0 * covariance + C2))
+                / ((mean_x * mean_x + mean_y * mean_y + C1) * (var_x + var_y + C2));
+            count += 1;
+        }
+    }
+    if count == 0 { 1.0 } else { sum / count as f64 }
+}
+
+/// Returns an image showing where two images differ
+///
+/// Matching pixels are shown as a dim gray version of the reference, mismatching ones in red, brighter the larger
+/// the difference.
+///
+/// # Arguments
+///
+/// * `expected` - Reference image
+/// * `actual` - Rendered image of the same size
+/// * `tolerance` - Largest difference of a channel still counting as equal
+///
+pub fn diff_image(expected: &Image, actual: &Image, tolerance: u8) -> Image {
+    let mut diff = Image::blank(expected.width, expected.height);
+    let luma = luma(expected);
+    for (i, (e, a)) in expected.bytes.chunks(4).zip(actual.bytes.chunks(4)).enumerate() {
+        let difference = e.iter().zip(a).map(|(e, a)| (*e as i32 - *a as i32).unsigned_abs()).max().unwrap();
+        let pixel = if difference > tolerance as u32 {
+            [(128 + difference / 2).min(255) as u8, 0, 0, 255]
+        } else {
+            let gray = (luma[i] / 4.0).round() as u8;
+            [gray, gray, gray, 255]
+        };
+        diff.bytes[i * 4..i * 4 + 4].copy_from_slice(&pixel);
+    }
+    diff
+}
+
+/// Returns the path of a reference image
+fn reference_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden").join(format!("{}.png", name))
+}
+
+/// Returns the directory the rendered and diff images of failed comparisons are written to
+fn failure_dir() -> PathBuf {
+    let target = env::var("CARGO_TARGET_DIR").map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target"));
+    target.join("golden")
+}
+
+/// Compares the Framebuffer of a Renderer against the reference image `tests/golden/<name>.png`
+///
+/// # Arguments
+///
+/// * `name` - Name of the reference image without extension
+/// * `renderer` - Renderer holding the rendered frame
+/// * `tolerance` - How far the frame may deviate from the reference
+///
+pub fn assert_golden(name: &str, renderer: &Renderer, tolerance: Tolerance) {
+    assert_golden_image(name, renderer.framebuffer().to_image(), tolerance);
+}
+
+/// Compares an image against the reference image `tests/golden/<name>.png`
+///
+/// Setting the environment variable `UPDATE_GOLDEN` writes the rendered image as new reference instead.
+/// On a mismatch the rendered image and a diff image are written to `target/golden` and the test fails,
+/// reporting the PSNR and SSIM.
+///
+/// # Arguments
+///
+/// * `name` - Name of the reference image without extension
+/// * `actual` - Rendered image
+/// * `tolerance` - How far the image may deviate from the reference
+///
+pub fn assert_golden_image(name: &str, actual: Image, tolerance: Tolerance) {
+    let reference = reference_path(name);
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        raster::save(&actual, reference.to_str().unwrap());
+        return;
+    }
+    if !reference.exists() {
+        panic!("Missing reference image {}, run the tests with UPDATE_GOLDEN=1 to create it", reference.display());
+    }
+    let expected = raster::open(reference.to_str().unwrap()).unwrap();
+    if (expected.width, expected.height) != (actual.width, actual.height) {
+        panic!("{}: rendered {}x{} pixels, the reference has {}x{}",
+               name, actual.width, actual.height, expected.width, expected.height);
+    }
+    let comparison = compare(&expected, &actual, tolerance.channel);
+    let allowed = (tolerance.pixels * (actual.width * actual.height) as f64).floor() as usize;
+    if comparison.mismatched > allowed {
+        let dir = failure_dir();
+        fs::create_dir_all(&dir).unwrap();
+        let rendered = dir.join(format!("{}.actual.png", name));
+        let diff = dir.join(format!("{}.diff.png", name));
+        raster::save(&actual, rendered.to_str().unwrap());
+        raster::save(&diff_image(&expected, &actual, tolerance.channel), diff.to_str().unwrap());
+        panic!("{}: {} pixels differ by up to {}, {} are allowed (PSNR {:.2} dB, SSIM {:.4})\nRendered: {}\nDiff: {}",
+               name, comparison.mismatched, comparison.max_difference, allowed, comparison.psnr, comparison.ssim,
+               rendered.display(), diff.display());
+    }
+}
@@ -0,0 +1,299 @@
+extern crate graphic_library;
+extern crate raster;
+
+mod golden;
+
+use raster::Color;
+use graphic_library::geometric::{Circle2D, Group, Line2D, Point2D, Transform2D, Triangle2D};
+use graphic_library::graphic::{Antialiasing, Background, BlendMode, Camera, Canvas, ColorSpace, DownsampleFilter, Framebuffer, Mask,
+                               Origin, PixelFormat, Renderer, SamplePattern, ShapeId, ToneMapping};
+use golden::{Tolerance, assert_golden, assert_golden_image, compare, diff_image};
+
+/// Width and height of the rendered images
+const SIZE: i32 = 64;
+
+/// Draws the Geometric-Objects of a Renderer in one of its draw modes
+type DrawMode = fn(&mut Renderer);
+
+/// Renders a scene in every draw mode and compares each frame against the reference `<name>_<mode>`
+///
+/// # Arguments
+///
+/// * `name` - Prefix of the reference images
+/// * `scene` - Adds the Geometric-Objects to a Renderer
+///
+fn assert_draw_modes(name: &str, scene: fn(&mut Renderer)) {
+    let modes: [(&str, DrawMode); 4] = [("draw", Renderer::draw),
+                                        ("draw_outline", Renderer::draw_outline),
+                                        ("draw_aa", Renderer::draw_aa),
+                                        ("draw_outline_aa", Renderer::draw_outline_aa)];
+    for &(mode, draw) in &modes {
+        let mut renderer = Renderer::new(SIZE, SIZE);
+        scene(&mut renderer);
+        renderer.clear();
+        draw(&mut renderer);
+        assert_golden(&format!("{}_{}", name, mode), &renderer, Tolerance::default());
+    }
+}
+
+/// Adds overlapping shapes of every kind, some translucent and some with gradients
+fn mixed_scene(renderer: &mut Renderer) {
+    renderer.add(Triangle2D::new(Point2D::new_color(4.0, 6.0, Color::red()),
+                                 Point2D::new_color(58.0, 12.5, Color::green()),
+                                 Point2D::new_color(20.3, 57.0, Color::blue())));
+    renderer.add(Circle2D::new(14.5, Point2D::new_color(40.25, 38.5, Color::rgba(255, 255, 0, 160))));
+    renderer.add(Line2D::new(Point2D::new_color(2.0, 60.0, Color::white()), Point2D::new_color(61.0, 3.5, Color::rgb(255, 0, 255))));
+}
+
+#[test]
+fn points() {
+    assert_draw_modes("points", |renderer| {
+        for i in 0..16 {
+            let (x, y) = (4.0 + i as f64 * 3.7, 4.0 + (i * i % 17) as f64 * 3.3);
+            renderer.add(Point2D::new_color(x, y, Color::rgba(255, (i * 16) as u8, 64, 128 + i as u8 * 8)));
+        }
+    });
+}
+
+#[test]
+fn lines() {
+    assert_draw_modes("lines", |renderer| {
+        renderer.add(Line2D::new(Point2D::new_color(4.0, 4.5, Color::white()), Point2D::new_color(60.0, 4.5, Color::white())));
+        renderer.add(Line2D::new(Point2D::new_color(4.5, 8.0, Color::red()), Point2D::new_color(4.5, 60.0, Color::blue())));
+        renderer.add(Line2D::new(Point2D::new_color(8.0, 8.0, Color::green()), Point2D::new_color(60.0, 60.0, Color::red())));
+        renderer.add(Line2D::new(Point2D::new_color(10.2, 40.7, Color::rgba(0, 255, 255, 200)), Point2D::new_color(61.3, 20.1, Color::rgba(255, 255, 0, 200))));
+        renderer.add(Line2D::new(Point2D::new_color(30.0, 62.0, Color::white()), Point2D::new_color(36.4, 12.0, Color::white())));
+    });
+}
+
+#[test]
+fn triangles() {
+    assert_draw_modes("triangles", |renderer| {
+        renderer.add(Triangle2D::new(Point2D::new_color(2.0, 2.0, Color::red()),
+                                     Point2D::new_color(40.0, 6.0, Color::green()),
+                                     Point2D::new_color(10.0, 44.0, Color::blue())));
+        renderer.add(Triangle2D::new(Point2D::new_color(24.5, 20.25, Color::rgba(255, 255, 255, 128)),
+                                     Point2D::new_color(62.0, 30.0, Color::rgba(255, 255, 255, 128)),
+                                     Point2D::new_color(30.0, 62.0, Color::rgba(255, 255, 255, 128))));
+        //A sliver thinner than a pixel
+        renderer.add(Triangle2D::new(Point2D::new_color(2.0, 60.0, Color::rgb(255, 128, 0)),
+                                     Point2D::new_color(62.0, 50.0, Color::rgb(255, 128, 0)),
+                                     Point2D::new_color(62.0, 50.6, Color::rgb(255, 128, 0))));
+    });
+}
+
+#[test]
+fn circles() {
+    assert_draw_modes("circles", |renderer| {
+        renderer.add(Circle2D::new(20.0, Point2D::new_color(24.25, 24.75, Color::rgb(200, 100, 30))));
+        renderer.add(Circle2D::new(12.5, Point2D::new_color(44.0, 44.0, Color::rgba(30, 100, 200, 160))));
+        renderer.add(Circle2D::new(1.5, Point2D::new_color(56.5, 8.5, Color::white())));
+        renderer.add(Circle2D::new(4.2, Point2D::new_color(10.0, 54.3, Color::green())));
+    });
+}
+
+#[test]
+fn groups() {
+    assert_draw_modes("groups", |renderer| {
+        let mut group = Group::new();
+        group.add(Triangle2D::new(Point2D::new_color(-16.0, -12.0, Color::red()),
+                                  Point2D::new_color(16.0, -12.0, Color::green()),
+                                  Point2D::new_color(0.0, 16.0, Color::blue())));
+        group.add(Circle2D::new(8.0, Point2D::new_color(0.0, 0.0, Color::white())));
+        group.set_transform(Transform2D::rotation(30.0).then(&Transform2D::translation(32.0, 32.0)));
+        group.set_opacity(0.75);
+        renderer.add(group);
+    });
+}
+
+#[test]
+fn supersampling() {
+    for &(name, filter) in &[("supersampling_box", DownsampleFilter::Box), ("supersampling_lanczos", DownsampleFilter::Lanczos)] {
+        let mut renderer = Renderer::new(SIZE, SIZE);
+        mixed_scene(&mut renderer);
+        renderer.set_antialiasing(Antialiasing::Supersampling { factor: 4, filter });
+        renderer.render_frame();
+        assert_golden(name, &renderer, Tolerance::default());
+    }
+}
+
+#[test]
+fn multisampling() {
+    let mut renderer = Renderer::new(SIZE, SIZE);
+    mixed_scene(&mut renderer);
+    renderer.set_antialiasing(Antialiasing::Multisampling(SamplePattern::default()));
+    renderer.render_frame();
+    assert_golden("multisampling", &renderer, Tolerance::default());
+}
+
+#[test]
+fn layers() {
+    let mut renderer = Renderer::new(SIZE, SIZE);
+    mixed_scene(&mut renderer);
+    let overlay = renderer.add_layer("overlay");
+    let square = renderer.add(Triangle2D::new(Point2D::new_color(0.0, 0.0, Color::rgb(0, 200, 255)),
+                                              Point2D::new_color(64.0, 0.0, Color::rgb(0, 200, 255)),
+                                              Point2D::new_color(0.0, 64.0, Color::rgb(0, 200, 255))));
+    renderer.set_layer(square, overlay);
+    renderer.set_layer_opacity(overlay, 0.5);
+    let multiply = renderer.add_layer("multiply");
+    let circle = renderer.add(Circle2D::new(24.0, Point2D::new_color(40.0, 40.0, Color::rgb(255, 128, 0))));
+    renderer.set_layer(circle, multiply);
+    renderer.set_layer_blend_mode(multiply, BlendMode::Multiply);
+    renderer.render_frame();
+    assert_golden("layers", &renderer, Tolerance::default());
+}
+
+#[test]
+fn backgrounds() {
+    //Gradients in sRGB only need exact arithmetic, the other color spaces depend on the precision of powf and cbrt
+    for &(name, color_space, tolerance) in &[("background_srgb", ColorSpace::Srgb, Tolerance::exact()),
+                                             ("background_oklab", ColorSpace::Oklab, Tolerance::default())] {
+        let mut renderer = Renderer::new(SIZE, SIZE);
+        renderer.set_background(Background::Gradient {
+            start: Point2D::new_color(0.0, 10.0, Color::blue()),
+            end: Point2D::new_color(63.0, 50.0, Color::rgb(255, 255, 0)),
+            color_space
+        });
+        renderer.render_frame();
+        assert_golden(name, &renderer, tolerance);
+    }
+}
+
+#[test]
+fn linear_blending() {
+    let mut renderer = Renderer::new(SIZE, SIZE);
+    mixed_scene(&mut renderer);
+    renderer.set_linear_blending(true);
+    renderer.clear();
+    renderer.draw_aa();
+    assert_golden("linear_blending", &renderer, Tolerance::default());
+}
+
+#[test]
+fn depth_test() {
+    let mut renderer = Renderer::new(SIZE, SIZE);
+    //Added front to back, the depth test still draws the nearest shape on top
+    renderer.add(Circle2D::new(16.0, Point2D::new_color(24.0, 24.0, Color::red()).with_depth(0.2)));
+    renderer.add(Triangle2D::new(Point2D::new_color(8.0, 8.0, Color::green()).with_depth(0.5),
+                                 Point2D::new_color(60.0, 20.0, Color::green()).with_depth(0.0),
+                                 Point2D::new_color(20.0, 60.0, Color::green()).with_depth(0.9)));
+    renderer.set_depth_test(true);
+    renderer.render_frame();
+    assert_golden("depth_test", &renderer, Tolerance::default());
+}
+
+#[test]
+fn camera_and_origin() {
+    let mut renderer = Renderer::new(SIZE, SIZE);
+    mixed_scene(&mut renderer);
+    renderer.set_camera(Some(Camera::new(32.0, 32.0, 1.5, 20.0)));
+    renderer.set_origin(Origin::BottomLeft);
+    renderer.render_frame();
+    assert_golden("camera_and_origin", &renderer, Tolerance::default());
+}
+
+#[test]
+fn hdr_tone_mapping() {
+    for &(name, tone_mapping) in &[("hdr_reinhard", ToneMapping::Reinhard), ("hdr_aces_filmic", ToneMapping::AcesFilmic)] {
+        let mut renderer = Renderer::new_hdr(SIZE, SIZE);
+        //Overlapping shapes add up above 1.0, where the tone mapping has to compress them
+        renderer.hdr_buffer_mut().unwrap().set_blend_mode(BlendMode::Additive);
+        mixed_scene(&mut renderer);
+        renderer.add(Circle2D::new(20.0, Point2D::new_color(24.0, 26.0, Color::rgb(255, 160, 64))));
+        renderer.set_tone_mapping(tone_mapping, 0.5);
+        renderer.render_frame();
+        assert_golden(name, &renderer, Tolerance::default());
+    }
+}
+
+#[test]
+fn mask() {
+    let mut renderer = Renderer::new(SIZE, SIZE);
+    mixed_scene(&mut renderer);
+    let mut mask = Mask::new(SIZE, SIZE);
+    for (_, geo) in renderer.iter() {
+        geo.draw_aa(&mut mask);
+    }
+    //The coverage is stored as gray, so the reference can be viewed like the others
+    let mut gray = Framebuffer::new(SIZE, SIZE);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let coverage = mask.coverage(x, y);
+            gray.set_pixel(x, y, &Color::rgb(coverage, coverage, coverage));
+        }
+    }
+    assert_golden_image("mask", gray.to_image(), Tolerance::default());
+}
+
+#[test]
+fn bgra8_framebuffer() {
+    let render = |framebuffer: Framebuffer| {
+        let mut renderer = Renderer::with_framebuffer(framebuffer);
+        mixed_scene(&mut renderer);
+        let overlay = renderer.add_layer("overlay");
+        let circle = renderer.add(Circle2D::new(18.0, Point2D::new_color(20.0, 44.0, Color::rgb(0, 200, 255))));
+        renderer.set_layer(circle, overlay);
+        renderer.set_layer_opacity(overlay, 0.5);
+        renderer.clear();
+        renderer.draw_aa();
+        renderer
+    };
+    //Rows padded to 80 pixels, the reference holds the colors in RGBA order like the others
+    let bgra = render(Framebuffer::with_format(SIZE, SIZE, 80 * 4, PixelFormat::Bgra8).unwrap());
+    assert_golden("bgra8", &bgra, Tolerance::default());
+    let rgba = render(Framebuffer::new(SIZE, SIZE));
+    assert_eq!(0, compare(&rgba.framebuffer().to_image(), &bgra.framebuffer().to_image(), 0).mismatched);
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn parallel_tiles() {
+    //Three by two tiles of 64 pixels, shapes cross the borders between them
+    let mut renderer = Renderer::new(SIZE * 3, SIZE * 2);
+    renderer.set_parallel(true);
+    renderer.add(Triangle2D::new(Point2D::new_color(4.0, 6.0, Color::red()),
+                                 Point2D::new_color(188.0, 30.5, Color::green()),
+                                 Point2D::new_color(40.3, 124.0, Color::blue())));
+    renderer.add(Circle2D::new(30.5, Point2D::new_color(128.25, 64.5, Color::rgba(255, 255, 0, 160))));
+    renderer.add(Line2D::new(Point2D::new_color(2.0, 125.0, Color::white()), Point2D::new_color(190.0, 3.5, Color::rgb(255, 0, 255))));
+    let mut group = Group::new();
+    group.add(Circle2D::new(20.0, Point2D::new_color(64.0, 64.0, Color::rgb(0, 200, 255))));
+    group.add(Circle2D::new(20.0, Point2D::new_color(84.0, 64.0, Color::rgb(0, 200, 255))));
+    group.set_opacity(0.5);
+    renderer.add(group);
+    let overlay = renderer.add_layer("overlay");
+    let line = renderer.add(Line2D::new(Point2D::new_color(0.0, 64.0, Color::white()), Point2D::new_color(191.0, 70.0, Color::white())));
+    renderer.set_layer(line, overlay);
+    renderer.set_layer_opacity(overlay, 0.5);
+    renderer.render_frame();
+    assert_golden("parallel_tiles", &renderer, Tolerance::default());
+}
+
+#[test]
+fn comparison_reports_differences() {
+    let mut renderer = Renderer::new(SIZE, SIZE);
+    mixed_scene(&mut renderer);
+    renderer.render_frame();
+    let expected = renderer.framebuffer().to_image();
+    let same = compare(&expected, &expected, 0);
+    assert_eq!((0, 0, 1.0), (same.max_difference, same.mismatched, same.ssim));
+    assert!(same.psnr.is_infinite());
+    //Moving the circle by a pixel is visible, but the images stay similar
+    let mut moved = Renderer::new(SIZE, SIZE);
+    mixed_scene(&mut moved);
+    let shapes: Vec<ShapeId> = moved.iter().map(|(id, _)| id).collect();
+    for id in shapes {
+        moved.get_mut(id).unwrap().transform(1.0, 0.0);
+    }
+    moved.render_frame();
+    let actual = moved.framebuffer().to_image();
+    let comparison = compare(&expected, &actual, 1);
+    assert!(comparison.mismatched > 0);
+    assert!(comparison.psnr < 30.0 && comparison.psnr > 5.0, "{}", comparison.psnr);
+    assert!(comparison.ssim < 1.0 && comparison.ssim > 0.3, "{}", comparison.ssim);
+    //Differences are red in the diff image
+    let diff = diff_image(&expected, &actual, 1);
+    let red = diff.bytes.chunks(4).filter(|p| p[0] >= 128 && p[1] == 0).count();
+    assert_eq!(comparison.mismatched, red);
+}